@@ -1,27 +0,0 @@
-/* tslint:disable */
-/* eslint-disable */
-export const memory: WebAssembly.Memory;
-export const __wbg_wasmworld_free: (a: number, b: number) => void;
-export const wasmworld_add_boid: (a: number, b: number, c: number, d: number) => void;
-export const wasmworld_add_boids: (a: number, b: number, c: number, d: number) => void;
-export const wasmworld_add_food: (a: number, b: number, c: number) => void;
-export const wasmworld_add_obstacle: (a: number, b: number, c: number) => void;
-export const wasmworld_add_predator: (a: number, b: number, c: number) => void;
-export const wasmworld_get_boid_data: (a: number) => [number, number];
-export const wasmworld_get_bug_data: (a: number) => [number, number];
-export const wasmworld_get_food_data: (a: number) => [number, number];
-export const wasmworld_get_obstacle_data: (a: number) => [number, number];
-export const wasmworld_get_predator_data: (a: number) => [number, number];
-export const wasmworld_get_stats: (a: number) => [number, number];
-export const wasmworld_height: (a: number) => number;
-export const wasmworld_new: (a: number, b: number, c: number) => number;
-export const wasmworld_remove_obstacle: (a: number, b: number, c: number) => void;
-export const wasmworld_reset: (a: number, b: number, c: number, d: number) => void;
-export const wasmworld_tick: (a: number, b: number, c: number, d: number, e: number) => void;
-export const wasmworld_width: (a: number) => number;
-export const init_panic_hook: () => void;
-export const __wbindgen_free: (a: number, b: number, c: number) => void;
-export const __wbindgen_malloc: (a: number, b: number) => number;
-export const __wbindgen_realloc: (a: number, b: number, c: number, d: number) => number;
-export const __wbindgen_externrefs: WebAssembly.Table;
-export const __wbindgen_start: () => void;
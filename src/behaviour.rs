@@ -0,0 +1,973 @@
+//! Steering behaviours run by `World` for every boid each tick.
+//!
+//! A behaviour turns one boid's view of its surroundings into a steering
+//! force. The `Pipeline` holds behaviours in order, each with a weight, a
+//! radius and an on/off switch that can all be changed at runtime, and sums
+//! their weighted forces.
+//!
+//! The standard flocking rules (`Separation`, `Alignment`, `Cohesion`,
+//! `Flee`, `AvoidObstacles`, `FollowScent`, `Territorial`) are ordinary implementations of `Behaviour`.
+//! Embedders add their own the same way and hand them to
+//! `World::register_behaviour`:
+//!
+//! ```
+//! use boids::{steer_towards, Agent, Behaviour, Environment, Neighbourhood, Vec2, World, MAX_FORCE, MAX_SPEED};
+//!
+//! /// Keep out of a circular no-fly zone
+//! struct AvoidZone { x: f32, y: f32, r: f32 }
+//!
+//! impl Behaviour for AvoidZone {
+//!     fn name(&self) -> &str { "avoid_zone" }
+//!
+//!     fn steer(&self, agent: &Agent, _: &Neighbourhood, _: &Environment, radius: f32) -> Vec2 {
+//!         let away = agent.position.sub(Vec2::new(self.x, self.y));
+//!         if away.mag() > self.r + radius { return Vec2::zero(); }
+//!         steer_towards(away, MAX_SPEED, agent.velocity, MAX_FORCE)
+//!     }
+//!
+//!     fn reach(&self, _radius: f32) -> f32 { 0.0 }
+//! }
+//!
+//! let mut world = World::new(900.0, 450.0, 50);
+//! world.register_behaviour(AvoidZone { x: 450.0, y: 225.0, r: 60.0 }, 2.0, 30.0);
+//! world.tick(0.0, 0.0, 0, 0.0);
+//! ```
+
+use std::any::Any;
+
+use crate::boid::{Boid, Mutations};
+use crate::obstacle::{Obstacles, AVOID_MARGIN, PANIC_AVOID_MARGIN};
+use crate::scent::{Channel, ScentGrid};
+use crate::territory::{Owner, TerritoryGrid};
+use crate::vision::Vision;
+use crate::vector::Vec2;
+#[cfg(feature = "simd")]
+use crate::simd::F32x4;
+
+pub const MAX_SPEED: f32 = 4.0;
+pub const MAX_FORCE: f32 = 0.15;
+/// Distance at which a brave boid notices a predator. Timid boids notice from
+/// up to half as far again.
+pub const FLEE_RADIUS: f32 = 100.0;
+/// Fear above this makes boids panic: they flee harder and give obstacles a wider berth
+pub const PANIC_FEAR: f32 = 0.3;
+/// Scent difference across a cell at which `FollowScent` steers at full force
+pub const SCENT_SATURATION: f32 = 0.5;
+
+/// Read-only snapshot of the boid being steered. Only boids in the normal
+/// flying state are steered.
+#[derive(Clone, Copy)]
+pub struct Agent {
+    pub position: Vec2,
+    pub velocity: Vec2,
+    pub species: u8,
+    pub is_hybrid: bool,
+    pub energy: f32,
+    pub max_energy: f32,
+    pub fatigue: f32,
+    pub age: u32,
+    pub mutations: Mutations,
+    // Traits, 0..1
+    pub bravery: f32,
+    pub hunger: f32,
+    pub laziness: f32,
+    pub sociability: f32,
+    /// What the boid can perceive: neighbours and predators outside this
+    /// are not passed to behaviours
+    pub vision: Vision,
+    pub in_shelter: bool,
+    /// 0..1, sensed from predators within `FLEE_RADIUS`. Zero in a shelter
+    /// unless the boid is bullied.
+    pub fear: f32,
+}
+
+impl Agent {
+    /// Snapshot of `boid` with its species' vision and the shelter and fear
+    /// state `World` sensed for it
+    pub fn new(boid: &Boid, vision: Vision, in_shelter: bool, fear: f32) -> Self {
+        Self {
+            position: boid.position,
+            velocity: boid.velocity,
+            species: boid.species,
+            is_hybrid: boid.is_hybrid,
+            energy: boid.energy,
+            max_energy: boid.max_energy,
+            fatigue: boid.fatigue,
+            age: boid.age,
+            mutations: boid.mutations,
+            bravery: boid.bravery,
+            hunger: boid.hunger,
+            laziness: boid.laziness,
+            sociability: boid.sociability,
+            vision,
+            in_shelter,
+            fear,
+        }
+    }
+}
+
+/// World state shared by every boid this tick
+pub struct Environment<'a> {
+    pub width: f32,
+    pub height: f32,
+    /// Simulation time in milliseconds
+    pub time: u32,
+    /// 0 = midnight, 1 = noon
+    pub day_phase: f32,
+    pub predators: &'a [(f32, f32)],
+    pub obstacles: &'a Obstacles,
+    pub shelters: &'a [(f32, f32, f32)],
+    pub scent: &'a ScentGrid,
+    pub territory: &'a TerritoryGrid,
+}
+
+/// Which per-neighbour quantity `Neighbourhood::sum` adds up
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Field {
+    Position,
+    Velocity,
+    /// Offset away from the neighbour divided by squared distance, so close
+    /// neighbours push hardest
+    Push,
+}
+
+/// Result of `Neighbourhood::sum`
+#[derive(Clone, Copy, Default, Debug, PartialEq)]
+pub struct Sum {
+    pub x: f32,
+    pub y: f32,
+    pub count: u32,
+}
+
+impl Sum {
+    /// Average of the summed values, or `None` if nothing was in range
+    pub fn mean(&self) -> Option<(f32, f32)> {
+        if self.count == 0 {
+            None
+        } else {
+            Some((self.x / self.count as f32, self.y / self.count as f32))
+        }
+    }
+}
+
+/// Another normal-state boid near the agent
+#[derive(Clone, Copy, Default)]
+pub struct Neighbour {
+    pub position: Vec2,
+    pub velocity: Vec2,
+    /// Offset from the neighbour to the agent
+    pub offset: Vec2,
+    pub dist_sq: f32,
+    /// Same species as the agent, or either of them is a hybrid
+    pub same_flock: bool,
+}
+
+impl Neighbour {
+    pub fn new(agent: &Agent, position: Vec2, velocity: Vec2, same_flock: bool) -> Self {
+        let dx = agent.position.x - position.x;
+        let dy = agent.position.y - position.y;
+        Self { position, velocity, offset: Vec2::new(dx, dy), dist_sq: dx * dx + dy * dy, same_flock }
+    }
+}
+
+/// Starlings keep track of about seven neighbours
+pub const TOPOLOGICAL_K: usize = 7;
+
+/// How a boid picks the flock-mates it aligns with and moves toward
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum NeighbourMode {
+    /// Everyone within the behaviour's radius
+    #[default]
+    Metric,
+    /// The `k` nearest flock-mates in view, however far, as starlings do
+    Topological(usize),
+}
+
+/// The neighbours of one boid, gathered once per boid per tick and shared by
+/// every behaviour. Only boids within the pipeline's largest
+/// `Behaviour::reach` are included.
+#[derive(Default)]
+pub struct Neighbourhood {
+    /// Only the first `len` slots are live; the rest is scratch space
+    slots: Vec<Neighbour>,
+    len: usize,
+    /// Squared distance that takes in exactly the nearest flock-mates, in
+    /// topological mode
+    flock_radius_sq: Option<f32>,
+    dist_scratch: Vec<f32>,
+}
+
+impl Neighbourhood {
+    /// Refill from at most `max` candidates. `fill` writes the neighbours it
+    /// keeps to the front of the slice it is given and returns how many.
+    /// Writing every candidate and advancing only on a keep avoids a
+    /// hard-to-predict branch per candidate.
+    #[inline]
+    pub(crate) fn refill(&mut self, max: usize, fill: impl FnOnce(&mut [Neighbour]) -> usize) {
+        if self.slots.len() < max {
+            self.slots.resize(max, Neighbour::default());
+        }
+        self.len = fill(&mut self.slots[..max]);
+        self.flock_radius_sq = None;
+    }
+
+    /// In topological mode, limit flock-only sums to the `k` nearest
+    /// flock-mates. Call after `refill`.
+    pub(crate) fn select_nearest_flock(&mut self, mode: NeighbourMode) {
+        let NeighbourMode::Topological(k) = mode else { return };
+        self.dist_scratch.clear();
+        self.dist_scratch.extend(self.slots[..self.len].iter().filter(|n| n.same_flock).map(|n| n.dist_sq));
+        self.flock_radius_sq = Some(match k {
+            0 => 0.0,
+            k if k >= self.dist_scratch.len() => f32::INFINITY,
+            k => self.dist_scratch.select_nth_unstable_by(k - 1, f32::total_cmp).1.next_up(),
+        });
+    }
+
+    pub fn as_slice(&self) -> &[Neighbour] {
+        &self.slots[..self.len]
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, Neighbour> {
+        self.as_slice().iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Sum `field` over neighbours closer than `radius`, optionally only
+    /// those in the agent's flock. In topological mode flock-only sums take
+    /// the nearest flock-mates instead and `radius` is ignored.
+    pub fn sum(&self, field: Field, radius: f32, flock_only: bool) -> Sum {
+        let r = match self.flock_radius_sq {
+            Some(r) if flock_only => r,
+            _ => radius * radius,
+        };
+        match field {
+            Field::Position => sum_field(self.as_slice(), r, flock_only, |n| (n.position.x, n.position.y)),
+            Field::Velocity => sum_field(self.as_slice(), r, flock_only, |n| (n.velocity.x, n.velocity.y)),
+            Field::Push => sum_field(self.as_slice(), r, flock_only, push),
+        }
+    }
+}
+
+impl From<Vec<Neighbour>> for Neighbourhood {
+    fn from(slots: Vec<Neighbour>) -> Self {
+        Self { len: slots.len(), slots, ..Self::default() }
+    }
+}
+
+#[inline]
+fn push(n: &Neighbour) -> (f32, f32) {
+    let inv = 1.0 / (n.dist_sq + 0.001);
+    (n.offset.x * inv, n.offset.y * inv)
+}
+
+#[inline]
+fn sum_field(list: &[Neighbour], radius_sq: f32, flock_only: bool, f: impl Fn(&Neighbour) -> (f32, f32)) -> Sum {
+    let mut sum = Sum::default();
+    for n in list {
+        // Branch-free: whether a neighbour is in range is close to a coin toss
+        let hit = n.dist_sq < radius_sq && (!flock_only || n.same_flock);
+        let (x, y) = f(n);
+        sum.x += if hit { x } else { 0.0 };
+        sum.y += if hit { y } else { 0.0 };
+        sum.count += hit as u32;
+    }
+    sum
+}
+
+/// Classic Reynolds steering: the force that turns `velocity` toward
+/// `direction` at `speed`, limited to `max_force`
+pub fn steer_towards(direction: Vec2, speed: f32, velocity: Vec2, max_force: f32) -> Vec2 {
+    let (mut x, mut y) = (direction.x, direction.y);
+    let m = (x * x + y * y).sqrt();
+    if m > 0.0 { x /= m; y /= m; }
+    x = x * speed - velocity.x;
+    y = y * speed - velocity.y;
+    let m = (x * x + y * y).sqrt();
+    if m > max_force { x = x / m * max_force; y = y / m * max_force; }
+    Vec2::new(x, y)
+}
+
+/// The three classic flocking rules. With the `simd` feature the pipeline
+/// computes all of them in one pass over the neighbourhood instead of calling
+/// their `steer` one at a time.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum FlockRule {
+    Separation,
+    Alignment,
+    Cohesion,
+}
+
+impl FlockRule {
+    /// The rule `behaviour` is, if it is one of the built-in types. Any other
+    /// behaviour is always steered through its own `steer`, even if it
+    /// shares a built-in's name.
+    fn of(behaviour: &dyn Behaviour) -> Option<Self> {
+        let any: &dyn Any = behaviour;
+        if any.is::<Separation>() {
+            Some(Self::Separation)
+        } else if any.is::<Alignment>() {
+            Some(Self::Alignment)
+        } else if any.is::<Cohesion>() {
+            Some(Self::Cohesion)
+        } else {
+            None
+        }
+    }
+}
+
+/// One steering rule. `steer` sees a read-only snapshot of the boid, its
+/// neighbourhood and the world, and returns a force that is added to the
+/// boid's acceleration after scaling by the pipeline weight. `radius` also
+/// comes from the pipeline entry, so both can be retuned at runtime.
+///
+/// Behaviours run in parallel with the `parallel` feature, hence `Send + Sync`.
+pub trait Behaviour: Any + Send + Sync {
+    /// Unique name used to look the behaviour up in a `Pipeline`
+    fn name(&self) -> &str;
+
+    fn steer(&self, agent: &Agent, neighbours: &Neighbourhood, env: &Environment, radius: f32) -> Vec2;
+
+    /// Farthest neighbour this behaviour looks at for a given `radius`. Boids
+    /// beyond the largest reach in the pipeline are left out of the
+    /// neighbourhood; return 0 if the behaviour ignores neighbours.
+    fn reach(&self, radius: f32) -> f32 {
+        radius
+    }
+}
+
+/// Move away from crowding neighbours of any species
+pub struct Separation;
+
+impl Behaviour for Separation {
+    fn name(&self) -> &str { "separation" }
+
+    fn steer(&self, agent: &Agent, neighbours: &Neighbourhood, _env: &Environment, radius: f32) -> Vec2 {
+        let Some((x, y)) = neighbours.sum(Field::Push, radius, false).mean() else { return Vec2::zero() };
+        steer_towards(Vec2::new(x, y), MAX_SPEED, agent.velocity, MAX_FORCE)
+    }
+}
+
+/// Match the heading of nearby flock-mates
+pub struct Alignment;
+
+impl Behaviour for Alignment {
+    fn name(&self) -> &str { "alignment" }
+
+    fn steer(&self, agent: &Agent, neighbours: &Neighbourhood, _env: &Environment, radius: f32) -> Vec2 {
+        let Some((x, y)) = neighbours.sum(Field::Velocity, radius, true).mean() else { return Vec2::zero() };
+        steer_towards(Vec2::new(x, y), MAX_SPEED, agent.velocity, MAX_FORCE)
+    }
+}
+
+/// Move toward the centre of nearby flock-mates. Flocks draw together at
+/// night, and sociable boids look further and pull harder.
+pub struct Cohesion;
+
+impl Behaviour for Cohesion {
+    fn name(&self) -> &str { "cohesion" }
+
+    fn steer(&self, agent: &Agent, neighbours: &Neighbourhood, env: &Environment, radius: f32) -> Vec2 {
+        let radius = cohesion_radius(agent, env, radius);
+        let Some((cx, cy)) = neighbours.sum(Field::Position, radius, true).mean() else { return Vec2::zero() };
+        let to_centre = Vec2::new(cx - agent.position.x, cy - agent.position.y);
+        steer_towards(to_centre, MAX_SPEED, agent.velocity, MAX_FORCE).mult(cohesion_pull(agent))
+    }
+
+    /// Darkest night and the most sociable boid
+    fn reach(&self, radius: f32) -> f32 {
+        radius * 1.8
+    }
+}
+
+#[inline]
+fn cohesion_radius(agent: &Agent, env: &Environment, radius: f32) -> f32 {
+    radius * (1.0 + (1.0 - env.day_phase) * 0.5 + agent.sociability * 0.3)
+}
+
+#[inline]
+fn cohesion_pull(agent: &Agent) -> f32 {
+    1.0 + agent.sociability * 0.3
+}
+
+/// simd128 version of `Separation`, `Alignment` and `Cohesion` together.
+/// One pass gathers the neighbourhood into four-lane batches and fills all
+/// three sums, then the three normalize/limit steps run side by side in one
+/// vector (lanes: separation, alignment, cohesion, unused). `radii` holds
+/// each rule's pipeline radius, or None for rules not to compute.
+#[cfg(feature = "simd")]
+fn flock_steer_simd(agent: &Agent, neighbours: &Neighbourhood, env: &Environment, radii: [Option<f32>; 3]) -> [Option<Vec2>; 3] {
+    let zero = F32x4::splat(0.0);
+    let one = F32x4::splat(1.0);
+    // Rules left out get a negative radius, which no neighbour is inside
+    let flock_radius_sq = |r: f32| neighbours.flock_radius_sq.unwrap_or(r * r);
+    let sep_r = F32x4::splat(radii[0].map_or(-1.0, |r| r * r));
+    let ali_r = F32x4::splat(radii[1].map_or(-1.0, flock_radius_sq));
+    let coh_r = F32x4::splat(radii[2].map_or(-1.0, |r| flock_radius_sq(cohesion_radius(agent, env, r))));
+    let [mut sep_x, mut sep_y, mut sep_ct] = [zero; 3];
+    let [mut ali_x, mut ali_y, mut ali_ct] = [zero; 3];
+    let [mut coh_x, mut coh_y, mut coh_ct] = [zero; 3];
+
+    for batch in neighbours.as_slice().chunks(4) {
+        // offset x, y, velocity x, y, position x, y, dist_sq, in flock; unused
+        // lanes are out of range
+        let mut lanes = [[0.0f32; 4]; 8];
+        lanes[6] = [f32::INFINITY; 4];
+        for (l, n) in batch.iter().enumerate() {
+            lanes[0][l] = n.offset.x;
+            lanes[1][l] = n.offset.y;
+            lanes[2][l] = n.velocity.x;
+            lanes[3][l] = n.velocity.y;
+            lanes[4][l] = n.position.x;
+            lanes[5][l] = n.position.y;
+            lanes[6][l] = n.dist_sq;
+            lanes[7][l] = if n.same_flock { 1.0 } else { 0.0 };
+        }
+        let [ox, oy, vx, vy, px, py, dsq, flock] = lanes.map(F32x4::from_array);
+        let flock = flock.gt(zero);
+
+        let sep = dsq.lt(sep_r);
+        let inv = one.div(dsq.add(F32x4::splat(0.001)));
+        sep_x = sep_x.add(sep.select(ox.mult(inv), zero));
+        sep_y = sep_y.add(sep.select(oy.mult(inv), zero));
+        sep_ct = sep_ct.add(sep.select(one, zero));
+
+        let ali = dsq.lt(ali_r).and(flock);
+        ali_x = ali_x.add(ali.select(vx, zero));
+        ali_y = ali_y.add(ali.select(vy, zero));
+        ali_ct = ali_ct.add(ali.select(one, zero));
+
+        let coh = dsq.lt(coh_r).and(flock);
+        coh_x = coh_x.add(coh.select(px, zero));
+        coh_y = coh_y.add(coh.select(py, zero));
+        coh_ct = coh_ct.add(coh.select(one, zero));
+    }
+
+    let counts = [sep_ct.sum(), ali_ct.sum(), coh_ct.sum()];
+    let cts = F32x4::new(counts[0], counts[1], counts[2], 0.0);
+    let cts = cts.gt(zero).select(cts, one);
+    let mut x = F32x4::new(sep_x.sum(), ali_x.sum(), coh_x.sum(), 0.0)
+        .div(cts)
+        .sub(F32x4::new(0.0, 0.0, agent.position.x, 0.0));
+    let mut y = F32x4::new(sep_y.sum(), ali_y.sum(), coh_y.sum(), 0.0)
+        .div(cts)
+        .sub(F32x4::new(0.0, 0.0, agent.position.y, 0.0));
+
+    // Normalize, scale to max speed and steer against current velocity
+    let m = x.mult(x).add(y.mult(y)).sqrt();
+    let nz = m.gt(zero);
+    x = nz.select(x.div(m), x);
+    y = nz.select(y.div(m), y);
+    x = x.mult(F32x4::splat(MAX_SPEED)).sub(F32x4::splat(agent.velocity.x));
+    y = y.mult(F32x4::splat(MAX_SPEED)).sub(F32x4::splat(agent.velocity.y));
+
+    // Limit to max force
+    let max_force = F32x4::splat(MAX_FORCE);
+    let m = x.mult(x).add(y.mult(y)).sqrt();
+    let over = m.gt(max_force);
+    x = over.select(x.div(m).mult(max_force), x);
+    y = over.select(y.div(m).mult(max_force), y);
+
+    let (x, y) = (x.to_array(), y.to_array());
+    let mult = [1.0, 1.0, cohesion_pull(agent)];
+    std::array::from_fn(|i| {
+        radii[i]?;
+        Some(if counts[i] > 0.0 { Vec2::new(x[i] * mult[i], y[i] * mult[i]) } else { Vec2::zero() })
+    })
+}
+
+/// Run from predators. Timid boids react from further away; a panicking boid
+/// flees several times harder. Boids in a shelter stay put, and predators in
+/// a boid's blind spot go unnoticed.
+pub struct Flee;
+
+impl Behaviour for Flee {
+    fn name(&self) -> &str { "flee" }
+
+    fn steer(&self, agent: &Agent, _neighbours: &Neighbourhood, env: &Environment, radius: f32) -> Vec2 {
+        if agent.in_shelter { return Vec2::zero(); }
+        let radius = radius + (1.0 - agent.bravery) * radius * 0.5;
+        let radius_sq = radius * radius;
+
+        let (mut fx, mut fy) = (0.0f32, 0.0f32);
+        for &(pred_x, pred_y) in env.predators {
+            let dx = agent.position.x - pred_x;
+            let dy = agent.position.y - pred_y;
+            let dsq = dx * dx + dy * dy;
+            if dsq < radius_sq && agent.vision.sees(agent.velocity, Vec2::new(-dx, -dy), dsq) {
+                let inv = 1.0 / (dsq.sqrt() + 0.001);
+                fx += dx * inv;
+                fy += dy * inv;
+            }
+        }
+        if fx == 0.0 && fy == 0.0 { return Vec2::zero(); }
+
+        let speed = MAX_SPEED * (1.5 + agent.bravery * 0.3);
+        let mult = if agent.fear > PANIC_FEAR { 4.0 } else { 1.0 } * (4.0 - agent.bravery);
+        steer_towards(Vec2::new(fx, fy), speed, agent.velocity, MAX_FORCE * 3.0).mult(mult)
+    }
+
+    fn reach(&self, _radius: f32) -> f32 {
+        0.0
+    }
+}
+
+/// Steer clear of obstacles within `radius` of their surface, or a wider
+/// margin when panicking, and of walls ahead
+pub struct AvoidObstacles;
+
+impl Behaviour for AvoidObstacles {
+    fn name(&self) -> &str { "avoid_obstacles" }
+
+    fn steer(&self, agent: &Agent, _neighbours: &Neighbourhood, env: &Environment, radius: f32) -> Vec2 {
+        let panic = agent.fear > PANIC_FEAR;
+        let margin = if panic { radius + (PANIC_AVOID_MARGIN - AVOID_MARGIN) } else { radius };
+        let (ox, oy) = env.obstacles.avoidance(agent.position.x, agent.position.y, agent.velocity, margin);
+        if ox == 0.0 && oy == 0.0 { return Vec2::zero(); }
+
+        let speed = MAX_SPEED * if panic { 1.5 } else { 1.0 };
+        let limit = MAX_FORCE * if panic { 3.0 } else { 2.0 };
+        let mult = if panic { 3.0 } else { 1.0 };
+        steer_towards(Vec2::new(ox, oy), speed, agent.velocity, limit).mult(mult)
+    }
+
+    fn reach(&self, _radius: f32) -> f32 {
+        0.0
+    }
+}
+
+/// Follow the species' scent trails: toward where food was found and away
+/// from where boids were caught or panicked. Hungry boids follow food
+/// harder, timid ones shy from danger harder. Faint trails steer gently.
+pub struct FollowScent;
+
+impl Behaviour for FollowScent {
+    fn name(&self) -> &str { "scent" }
+
+    fn steer(&self, agent: &Agent, _neighbours: &Neighbourhood, env: &Environment, _radius: f32) -> Vec2 {
+        let (x, y) = (agent.position.x, agent.position.y);
+        let food = env.scent.gradient(Channel::Food(agent.species), x, y).mult(0.5 + agent.hunger);
+        let danger = env.scent.gradient(Channel::Danger(agent.species), x, y).mult(2.0 - agent.bravery);
+        let pull = food.sub(danger);
+        let strength = (pull.mag() / SCENT_SATURATION).min(1.0);
+        if strength == 0.0 { return Vec2::zero(); }
+        steer_towards(pull, MAX_SPEED, agent.velocity, MAX_FORCE).mult(strength)
+    }
+
+    fn reach(&self, _radius: f32) -> f32 {
+        0.0
+    }
+}
+
+/// Keep out of ground the other species holds. On home ground, AGGRESSIVE
+/// boids also chase off the nearest intruder of the other species within
+/// `radius`. Hybrids belong to neither side and ignore territory.
+pub struct Territorial;
+
+impl Behaviour for Territorial {
+    fn name(&self) -> &str { "territory" }
+
+    fn steer(&self, agent: &Agent, neighbours: &Neighbourhood, env: &Environment, radius: f32) -> Vec2 {
+        if agent.is_hybrid { return Vec2::zero(); }
+        let (x, y) = (agent.position.x, agent.position.y);
+        let home = Owner::Species(agent.species);
+
+        let foreign = env.territory.pressure(x, y, |o| matches!(o, Owner::Species(_)) && o != home);
+        let mut force = if foreign.mag_sq() > 0.0 {
+            steer_towards(foreign.mult(-1.0), MAX_SPEED, agent.velocity, MAX_FORCE).mult(foreign.mag().min(1.0))
+        } else {
+            Vec2::zero()
+        };
+
+        if agent.mutations.has(Mutations::AGGRESSIVE) && env.territory.owner_at(x, y) == Some(home) {
+            let intruder = neighbours.iter()
+                .filter(|n| !n.same_flock && n.dist_sq < radius * radius)
+                .filter(|n| env.territory.owner_at(n.position.x, n.position.y) == Some(home))
+                .min_by(|a, b| a.dist_sq.total_cmp(&b.dist_sq));
+            if let Some(n) = intruder {
+                let chase = n.position.sub(agent.position);
+                force.add_mut(steer_towards(chase, MAX_SPEED * 1.2, agent.velocity, MAX_FORCE * 2.0));
+            }
+        }
+        force
+    }
+}
+
+/// A behaviour and its tuning within a `Pipeline`
+pub struct Entry {
+    pub behaviour: Box<dyn Behaviour>,
+    pub weight: f32,
+    pub radius: f32,
+    pub enabled: bool,
+}
+
+/// Ordered list of behaviours whose weighted forces are summed per boid
+pub struct Pipeline {
+    entries: Vec<Entry>,
+}
+
+impl Pipeline {
+    /// A pipeline with no behaviours
+    pub fn empty() -> Self {
+        Self { entries: Vec::new() }
+    }
+
+    /// The built-in flocking rules with their default tuning
+    pub fn standard() -> Self {
+        let mut p = Self::empty();
+        p.push(Box::new(Separation), 1.8, 25.0);
+        p.push(Box::new(Alignment), 1.0, 50.0);
+        p.push(Box::new(Cohesion), 1.0, 50.0);
+        p.push(Box::new(Flee), 1.0, FLEE_RADIUS);
+        p.push(Box::new(AvoidObstacles), 1.0, AVOID_MARGIN);
+        p.push(Box::new(FollowScent), 0.5, 0.0);
+        p.push(Box::new(Territorial), 1.0, 60.0);
+        p
+    }
+
+    /// Append a behaviour. One with the same name as an existing entry
+    /// replaces it in place.
+    pub fn push(&mut self, behaviour: Box<dyn Behaviour>, weight: f32, radius: f32) {
+        let entry = Entry { behaviour, weight, radius, enabled: true };
+        match self.position(entry.behaviour.name()) {
+            Some(i) => self.entries[i] = entry,
+            None => self.entries.push(entry),
+        }
+    }
+
+    pub fn remove(&mut self, name: &str) -> Option<Entry> {
+        let i = self.position(name)?;
+        Some(self.entries.remove(i))
+    }
+
+    pub fn entries(&self) -> &[Entry] {
+        &self.entries
+    }
+
+    pub fn get_mut(&mut self, name: &str) -> Option<&mut Entry> {
+        self.entries.iter_mut().find(|e| e.behaviour.name() == name)
+    }
+
+    fn position(&self, name: &str) -> Option<usize> {
+        self.entries.iter().position(|e| e.behaviour.name() == name)
+    }
+
+    /// Returns false if no behaviour has that name
+    pub fn set_weight(&mut self, name: &str, weight: f32) -> bool {
+        self.get_mut(name).map(|e| e.weight = weight).is_some()
+    }
+
+    /// Returns false if no behaviour has that name
+    pub fn set_radius(&mut self, name: &str, radius: f32) -> bool {
+        self.get_mut(name).map(|e| e.radius = radius).is_some()
+    }
+
+    /// Returns false if no behaviour has that name
+    pub fn set_enabled(&mut self, name: &str, enabled: bool) -> bool {
+        self.get_mut(name).map(|e| e.enabled = enabled).is_some()
+    }
+
+    /// Move a behaviour to `index` (clamped to the end). Returns false if no
+    /// behaviour has that name.
+    pub fn move_to(&mut self, name: &str, index: usize) -> bool {
+        let Some(i) = self.position(name) else { return false };
+        let entry = self.entries.remove(i);
+        self.entries.insert(index.min(self.entries.len()), entry);
+        true
+    }
+
+    /// Largest neighbour reach among the enabled behaviours
+    pub fn reach(&self) -> f32 {
+        self.entries.iter()
+            .filter(|e| e.enabled)
+            .map(|e| e.behaviour.reach(e.radius))
+            .fold(0.0, f32::max)
+    }
+
+    /// Weighted sum of every enabled behaviour's force, in pipeline order
+    pub fn steer(&self, agent: &Agent, neighbours: &Neighbourhood, env: &Environment) -> Vec2 {
+        #[cfg(feature = "simd")]
+        let fused = flock_steer_simd(agent, neighbours, env, self.flock_radii());
+        #[cfg(not(feature = "simd"))]
+        let fused = [None; 3];
+        self.steer_with(agent, neighbours, env, fused)
+    }
+
+    /// Radius of each enabled flocking rule, indexed by `FlockRule`
+    #[cfg(feature = "simd")]
+    fn flock_radii(&self) -> [Option<f32>; 3] {
+        let mut radii = [None; 3];
+        for e in self.entries.iter().filter(|e| e.enabled) {
+            if let Some(rule) = FlockRule::of(e.behaviour.as_ref()) {
+                radii[rule as usize] = Some(e.radius);
+            }
+        }
+        radii
+    }
+
+    /// `steer`, taking the flocking rules' forces from `fused` where given
+    fn steer_with(&self, agent: &Agent, neighbours: &Neighbourhood, env: &Environment, fused: [Option<Vec2>; 3]) -> Vec2 {
+        let (mut ax, mut ay) = (0.0f32, 0.0f32);
+        for e in self.entries.iter().filter(|e| e.enabled) {
+            let f = match FlockRule::of(e.behaviour.as_ref()).and_then(|r| fused[r as usize]) {
+                Some(f) => f,
+                None => e.behaviour.steer(agent, neighbours, env, e.radius),
+            };
+            ax += f.x * e.weight;
+            ay += f.y * e.weight;
+        }
+        Vec2::new(ax, ay)
+    }
+}
+
+impl Default for Pipeline {
+    fn default() -> Self {
+        Self::standard()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "simd")]
+    fn lcg(seed: &mut u32) -> f32 {
+        *seed = seed.wrapping_mul(1664525).wrapping_add(1013904223);
+        (*seed >> 8) as f32 / (1u32 << 24) as f32
+    }
+
+    fn agent(x: f32, y: f32, vx: f32, vy: f32) -> Agent {
+        Agent {
+            position: Vec2::new(x, y),
+            velocity: Vec2::new(vx, vy),
+            bravery: 0.5,
+            sociability: 0.5,
+            ..Agent::new(&Boid::new(x, y, 0, false), Vision::new(360.0, 1000.0), false, 0.0)
+        }
+    }
+
+    /// Empty obstacles, scent and territory for a 900 x 450 world
+    struct Ground {
+        obstacles: Obstacles,
+        scent: ScentGrid,
+        territory: TerritoryGrid,
+    }
+
+    impl Ground {
+        fn new() -> Self {
+            Self {
+                obstacles: Obstacles::new(),
+                scent: ScentGrid::new(900.0, 450.0),
+                territory: TerritoryGrid::new(900.0, 450.0),
+            }
+        }
+    }
+
+    /// Noon over `ground`, with no predators or shelters
+    fn env(ground: &Ground) -> Environment<'_> {
+        Environment {
+            width: 900.0,
+            height: 450.0,
+            time: 0,
+            day_phase: 1.0,
+            predators: &[],
+            obstacles: &ground.obstacles,
+            shelters: &[],
+            scent: &ground.scent,
+            territory: &ground.territory,
+        }
+    }
+
+    #[test]
+    fn separation_pushes_away_from_close_neighbour() {
+        let me = agent(100.0, 100.0, 0.0, 0.0);
+        let n = Neighbourhood::from(vec![Neighbour::new(&me, Vec2::new(110.0, 100.0), Vec2::zero(), true)]);
+        let ground = Ground::new();
+        let env = env(&ground);
+
+        let f = Separation.steer(&me, &n, &env, 25.0);
+        assert!(f.x < 0.0 && f.y == 0.0);
+        assert_eq!(Separation.steer(&me, &n, &env, 5.0).x, 0.0);
+    }
+
+    #[test]
+    fn pipeline_weights_and_switches() {
+        let me = agent(100.0, 100.0, 1.0, 0.0);
+        let n = Neighbourhood::from(vec![Neighbour::new(&me, Vec2::new(110.0, 100.0), Vec2::new(0.0, 2.0), true)]);
+        let ground = Ground::new();
+        let env = env(&ground);
+
+        let mut p = Pipeline::empty();
+        p.push(Box::new(Alignment), 1.0, 50.0);
+        let base = p.steer(&me, &n, &env);
+        assert!(p.set_weight("alignment", 2.0));
+        let doubled = p.steer(&me, &n, &env);
+        assert_eq!((doubled.x, doubled.y), (base.x * 2.0, base.y * 2.0));
+        p.set_enabled("alignment", false);
+        assert_eq!(p.steer(&me, &n, &env).mag_sq(), 0.0);
+        assert!(!p.set_weight("missing", 1.0));
+    }
+
+    #[test]
+    fn flee_runs_from_predators_in_range() {
+        let me = agent(100.0, 100.0, 1.0, 0.0);
+        let n = Neighbourhood::default();
+        let ground = Ground::new();
+        let predators = [(130.0, 100.0)];
+        let env = Environment { predators: &predators, ..env(&ground) };
+
+        let f = Flee.steer(&me, &n, &env, FLEE_RADIUS);
+        assert!(f.x < 0.0 && f.y.abs() < 1e-6, "{f:?}");
+        // Panic makes it run harder
+        let panicked = Flee.steer(&Agent { fear: 1.0, ..me }, &n, &env, FLEE_RADIUS);
+        assert!(panicked.x < f.x);
+        assert_eq!(Flee.steer(&Agent { in_shelter: true, ..me }, &n, &env, FLEE_RADIUS).mag_sq(), 0.0);
+        assert_eq!(Flee.steer(&me, &n, &env, 10.0).mag_sq(), 0.0);
+    }
+
+    #[test]
+    fn avoid_obstacles_turns_from_nearby_rocks() {
+        let me = agent(100.0, 100.0, 0.0, 1.0);
+        let n = Neighbourhood::default();
+        let mut ground = Ground::new();
+        ground.obstacles.add(100.0, 130.0, 10.0);
+
+        let f = AvoidObstacles.steer(&me, &n, &env(&ground), AVOID_MARGIN);
+        assert!(f.y < 0.0 && f.x.abs() < 1e-6, "{f:?}");
+        assert_eq!(AvoidObstacles.steer(&me, &n, &env(&ground), 5.0).mag_sq(), 0.0);
+    }
+
+    #[test]
+    fn follow_scent_seeks_food_and_shuns_danger() {
+        let me = agent(100.0, 100.0, 0.0, 0.0);
+        let n = Neighbourhood::default();
+        let mut ground = Ground::new();
+        ground.scent.deposit(Channel::Food(0), 130.0, 100.0, 1.0);
+        let f = FollowScent.steer(&me, &n, &env(&ground), 0.0);
+        assert!(f.x > 0.0 && f.y == 0.0, "{f:?}");
+
+        // Another species' food means nothing
+        let other = Agent { species: 1, ..me };
+        assert_eq!(FollowScent.steer(&other, &n, &env(&ground), 0.0).mag_sq(), 0.0);
+
+        ground.scent.deposit(Channel::Danger(0), 100.0, 130.0, 1.0);
+        let f = FollowScent.steer(&me, &n, &env(&ground), 0.0);
+        assert!(f.x > 0.0 && f.y < 0.0, "{f:?}");
+    }
+
+    #[test]
+    fn territorial_keeps_out_of_the_other_species_ground() {
+        let me = agent(125.0, 125.0, 0.0, 0.0);
+        let n = Neighbourhood::default();
+        let mut ground = Ground::new();
+        // Species 1 holds the column of cells to the east
+        for _ in 0..500 {
+            for y in [75.0, 125.0, 175.0] {
+                ground.territory.mark(Owner::Species(1), 175.0, y);
+            }
+        }
+
+        let f = Territorial.steer(&me, &n, &env(&ground), 60.0);
+        assert!(f.x < 0.0 && f.y.abs() < 1e-6, "{f:?}");
+        let owner = Agent { species: 1, ..me };
+        assert_eq!(Territorial.steer(&owner, &n, &env(&ground), 60.0).mag_sq(), 0.0);
+        let hybrid = Agent { is_hybrid: true, ..me };
+        assert_eq!(Territorial.steer(&hybrid, &n, &env(&ground), 60.0).mag_sq(), 0.0);
+    }
+
+    /// A custom behaviour that always pushes the same way
+    struct Constant(&'static str, Vec2);
+
+    impl Behaviour for Constant {
+        fn name(&self) -> &str { self.0 }
+
+        fn steer(&self, _agent: &Agent, _neighbours: &Neighbourhood, _env: &Environment, _radius: f32) -> Vec2 {
+            self.1
+        }
+    }
+
+    #[test]
+    fn custom_behaviours_are_always_steered() {
+        let me = agent(100.0, 100.0, 1.0, 0.0);
+        let n = Neighbourhood::from(vec![
+            Neighbour::new(&me, Vec2::new(110.0, 100.0), Vec2::new(0.0, 2.0), true),
+            Neighbour::new(&me, Vec2::new(100.0, 130.0), Vec2::new(1.0, 1.0), true),
+        ]);
+        let ground = Ground::new();
+        let env = env(&ground);
+        let close = |a: Vec2, b: Vec2| (a.x - b.x).abs() < 1e-4 && (a.y - b.y).abs() < 1e-4;
+
+        let mut p = Pipeline::empty();
+        p.push(Box::new(Separation), 1.0, 25.0);
+        p.push(Box::new(Alignment), 1.0, 50.0);
+        p.push(Box::new(Cohesion), 1.0, 50.0);
+        let base = p.steer(&me, &n, &env);
+        assert!(base.mag_sq() > 0.0);
+
+        p.push(Box::new(Constant("nudge", Vec2::new(0.0, 0.5))), 2.0, 0.0);
+        let nudged = p.steer(&me, &n, &env);
+        assert!(close(nudged, base.add(Vec2::new(0.0, 1.0))), "{nudged:?}");
+
+        // Taking a built-in's name does not route it through the fused kernel
+        p.push(Box::new(Constant("separation", Vec2::new(3.0, 0.0))), 1.0, 25.0);
+        let expected = Alignment.steer(&me, &n, &env, 50.0)
+            .add(Cohesion.steer(&me, &n, &env, 50.0))
+            .add(Vec2::new(3.0, 1.0));
+        let replaced = p.steer(&me, &n, &env);
+        assert!(close(replaced, expected), "{replaced:?} vs {expected:?}");
+    }
+
+    #[test]
+    fn topological_keeps_k_nearest_flockmates_with_ties() {
+        let me = agent(100.0, 100.0, 0.0, 0.0);
+        let at = |dx: f32, same_flock| Neighbour::new(&me, Vec2::new(100.0 + dx, 100.0), Vec2::zero(), same_flock);
+        let list = vec![at(5.0, false), at(30.0, true), at(20.0, true), at(10.0, true), at(-20.0, true), at(20.0, true)];
+        let mut n = Neighbourhood::from(list);
+        // The second nearest flock-mate is at 20, and so are two others
+        n.select_nearest_flock(NeighbourMode::Topological(2));
+        assert_eq!(n.sum(Field::Position, 1000.0, true).count, 4);
+        // Sums over every species still go by radius
+        assert_eq!(n.sum(Field::Position, 25.0, false).count, 5);
+        n.select_nearest_flock(NeighbourMode::Topological(0));
+        assert_eq!(n.sum(Field::Position, 1000.0, true).count, 0);
+        n.select_nearest_flock(NeighbourMode::Topological(TOPOLOGICAL_K));
+        assert_eq!(n.sum(Field::Position, 1.0, true).count, 5);
+    }
+
+    #[cfg(feature = "simd")]
+    #[test]
+    fn simd_steering_matches_scalar() {
+        let ground = Ground::new();
+        let mut p = Pipeline::standard();
+        let mut seed = 42;
+        for round in 0..200 {
+            let count = round % 37;
+            let mut me = agent(60.0, 60.0, (lcg(&mut seed) - 0.5) * 8.0, (lcg(&mut seed) - 0.5) * 8.0);
+            me.sociability = lcg(&mut seed);
+            let list: Vec<_> = (0..count).map(|_| {
+                let p = Vec2::new(lcg(&mut seed) * 120.0, lcg(&mut seed) * 120.0);
+                let v = Vec2::new((lcg(&mut seed) - 0.5) * 8.0, (lcg(&mut seed) - 0.5) * 8.0);
+                Neighbour::new(&me, p, v, lcg(&mut seed) < 0.6)
+            }).collect();
+            let mut n = Neighbourhood::from(list);
+            if round % 3 == 0 {
+                n.select_nearest_flock(NeighbourMode::Topological(TOPOLOGICAL_K));
+            }
+            let env = Environment { day_phase: lcg(&mut seed), ..env(&ground) };
+            p.set_radius("separation", 20.0 + lcg(&mut seed) * 20.0);
+            p.set_enabled("alignment", round % 5 != 0);
+
+            let s = p.steer_with(&me, &n, &env, [None; 3]);
+            let v = p.steer(&me, &n, &env);
+            assert!((s.x - v.x).abs() < 1e-4 && (s.y - v.y).abs() < 1e-4,
+                "round {round}: scalar ({}, {}) vs simd ({}, {})", s.x, s.y, v.x, v.y);
+        }
+    }
+}
@@ -0,0 +1,469 @@
+//! Headless runner: steps a `World` without a browser and prints metrics.
+//!
+//! ```text
+//! boids-cli --boids 150 --predators 3 --seed 7 --ticks 50000 --every 500 --format csv
+//! boids-cli sweep --predators 1..10 --bug-mult 0.5,1,2 --shelter-radius 30..70:20 --seeds 1..5
+//! boids-cli view --boids 200 --predators 2 --frame-every 2
+//! ```
+
+use std::io::{self, BufWriter, Write};
+use std::process;
+use std::str::FromStr;
+
+use boids::{parse_values, Metrics, NeighbourMode, Scenario, Summary, SvgOptions, Sweep, TerrainMap, World};
+#[cfg(feature = "tui")]
+use boids::TOPOLOGICAL_K;
+#[cfg(any(feature = "render", feature = "tui"))]
+use boids::STEP_MS;
+#[cfg(feature = "render")]
+use boids::{draw_world, load_png, save_png, Canvas, GifWriter};
+#[cfg(feature = "render")]
+use std::path::{Path, PathBuf};
+#[cfg(feature = "tui")]
+use boids::TermCanvas;
+
+const USAGE: &str = "\
+Usage: boids-cli [options]
+       boids-cli sweep [sweep options]
+       boids-cli view [options]
+
+Options:
+  --width <px>        World width (default 900)
+  --height <px>       World height (default 450)
+  --boids <n>         Starting boids (default 60)
+  --predators <n>     Predators placed at random (default 0)
+  --seed <n>          Random seed (default 1)
+  --ticks <n>         Ticks to run (default 10000)
+  --every <n>         Print metrics every n ticks (default 100)
+  --format <fmt>      csv or ndjson (default csv)
+  --scenario <file>   Scenario file applied after setup
+  --terrain <file>    Terrain map: text with one character per cell
+                      (. meadow, f forest, ~ water, # rock, s safe) or,
+                      with the `render` feature, a PNG with one pixel
+                      per cell in the zone colours
+  --png-dir <dir>     Write PNG frames into dir (needs the `render` feature)
+  --gif <file>        Write an animated GIF (needs the `render` feature)
+  --frame-every <n>   Render a frame every n ticks (default 4)
+  --svg <file>        Write an SVG snapshot of the final state
+  --topological <k>   Align and cohere with the k nearest flock-mates
+                      instead of everyone within a radius
+  -h, --help          Show this help
+
+Sweep options (lists: 3 | 1,2,5 | 1..10 | 0.5..2:0.5):
+  --predators <list>      Predator counts (default 0..5)
+  --bug-mult <list>       Bug spawn rate multipliers (default 1)
+  --shelter-radius <list> Shelter radii (default 50)
+  --seeds <list>          Seeds per configuration (default 1..3)
+  --width, --height, --boids, --ticks as above
+Prints one CSV row per configuration.
+
+View (needs the `tui` feature) draws the world live in the terminal,
+advancing --frame-every ticks per redraw. Keys: space pause, s step,
+p predator at centre, t toggle metric/topological neighbours, q quit.";
+
+#[derive(Clone, Copy, PartialEq)]
+enum Format {
+    Csv,
+    Ndjson,
+}
+
+struct Options {
+    width: f32,
+    height: f32,
+    boids: u32,
+    predators: u32,
+    seed: u32,
+    ticks: u32,
+    every: u32,
+    format: Format,
+    scenario: Option<String>,
+    terrain: Option<String>,
+    png_dir: Option<String>,
+    gif: Option<String>,
+    frame_every: u32,
+    svg: Option<String>,
+    topological: Option<usize>,
+}
+
+fn parse<T: FromStr>(flag: &str, value: &str) -> Result<T, String> {
+    value.parse().map_err(|_| format!("invalid value for {}: '{}'", flag, value))
+}
+
+fn parse_args(args: Vec<String>) -> Result<Options, String> {
+    let mut opts = Options {
+        width: 900.0,
+        height: 450.0,
+        boids: 60,
+        predators: 0,
+        seed: 1,
+        ticks: 10_000,
+        every: 100,
+        format: Format::Csv,
+        scenario: None,
+        terrain: None,
+        png_dir: None,
+        gif: None,
+        frame_every: 4,
+        svg: None,
+        topological: None,
+    };
+
+    let mut args = args.into_iter();
+    while let Some(flag) = args.next() {
+        if flag == "-h" || flag == "--help" {
+            println!("{}", USAGE);
+            process::exit(0);
+        }
+        let value = args.next().ok_or_else(|| format!("{} needs a value", flag))?;
+        match flag.as_str() {
+            "--width" => opts.width = parse(&flag, &value)?,
+            "--height" => opts.height = parse(&flag, &value)?,
+            "--boids" => opts.boids = parse(&flag, &value)?,
+            "--predators" => opts.predators = parse(&flag, &value)?,
+            "--seed" => opts.seed = parse(&flag, &value)?,
+            "--ticks" => opts.ticks = parse(&flag, &value)?,
+            "--every" => opts.every = parse::<u32>(&flag, &value)?.max(1),
+            "--format" => {
+                opts.format = match value.as_str() {
+                    "csv" => Format::Csv,
+                    "ndjson" => Format::Ndjson,
+                    _ => return Err(format!("unknown format '{}' (expected csv or ndjson)", value)),
+                }
+            }
+            "--scenario" => opts.scenario = Some(value),
+            "--terrain" => opts.terrain = Some(value),
+            "--png-dir" => opts.png_dir = Some(value),
+            "--gif" => opts.gif = Some(value),
+            "--frame-every" => opts.frame_every = parse::<u32>(&flag, &value)?.max(1),
+            "--svg" => opts.svg = Some(value),
+            "--topological" => opts.topological = Some(parse(&flag, &value)?),
+            _ => return Err(format!("unknown option '{}'", flag)),
+        }
+    }
+    if !cfg!(feature = "render") && (opts.png_dir.is_some() || opts.gif.is_some()) {
+        return Err("frame output needs boids-cli built with `--features render`".into());
+    }
+    Ok(opts)
+}
+
+fn parse_sweep_args(args: Vec<String>) -> Result<Sweep, String> {
+    let mut sweep = Sweep {
+        width: 900.0,
+        height: 450.0,
+        boids: 60,
+        ticks: 10_000,
+        predators: (0..=5).collect(),
+        bug_mult: vec![1.0],
+        shelter_radius: vec![50.0],
+        seeds: (1..=3).collect(),
+    };
+
+    let mut args = args.into_iter();
+    while let Some(flag) = args.next() {
+        if flag == "-h" || flag == "--help" {
+            println!("{}", USAGE);
+            process::exit(0);
+        }
+        let value = args.next().ok_or_else(|| format!("{} needs a value", flag))?;
+        let list_err = |e: String| format!("{}: {}", flag, e);
+        match flag.as_str() {
+            "--width" => sweep.width = parse(&flag, &value)?,
+            "--height" => sweep.height = parse(&flag, &value)?,
+            "--boids" => sweep.boids = parse(&flag, &value)?,
+            "--ticks" => sweep.ticks = parse(&flag, &value)?,
+            "--predators" => sweep.predators = parse_values(&value).map_err(list_err)?,
+            "--bug-mult" => sweep.bug_mult = parse_values(&value).map_err(list_err)?,
+            "--shelter-radius" => sweep.shelter_radius = parse_values(&value).map_err(list_err)?,
+            "--seeds" => sweep.seeds = parse_values(&value).map_err(list_err)?,
+            _ => return Err(format!("unknown sweep option '{}'", flag)),
+        }
+    }
+    Ok(sweep)
+}
+
+fn run_sweep(args: Vec<String>) -> io::Result<()> {
+    let sweep = parse_sweep_args(args).unwrap_or_else(|e| {
+        eprintln!("error: {}\n\n{}", e, USAGE);
+        process::exit(2);
+    });
+
+    let configs = sweep.configs();
+    let mut out = BufWriter::new(io::stdout().lock());
+    writeln!(out, "{}", Summary::CSV_HEADER)?;
+    for (i, config) in configs.iter().enumerate() {
+        eprintln!("[{}/{}] predators={} bug_mult={} shelter_radius={}",
+            i + 1, configs.len(), config.predators, config.bug_mult, config.shelter_radius);
+        writeln!(out, "{}", sweep.run(*config).to_csv())?;
+        out.flush()?;
+    }
+    Ok(())
+}
+
+/// Seed and populate a world from the command line, then apply the scenario
+fn build_world(opts: &Options) -> World {
+    let scenario = match &opts.scenario {
+        Some(path) => {
+            let text = std::fs::read_to_string(path).unwrap_or_else(|e| {
+                eprintln!("error: cannot read {}: {}", path, e);
+                process::exit(1);
+            });
+            Scenario::parse(&text).unwrap_or_else(|e| {
+                eprintln!("error: {}: {}", path, e);
+                process::exit(1);
+            })
+        }
+        None => Scenario::default(),
+    };
+
+    let terrain = match &opts.terrain {
+        Some(path) => load_terrain(path, opts.width, opts.height).unwrap_or_else(|e| {
+            eprintln!("error: {}: {}", path, e);
+            process::exit(1);
+        }),
+        None => TerrainMap::standard(opts.width, opts.height),
+    };
+
+    boids::set_seed(opts.seed);
+    let mut world = World::with_terrain(opts.width, opts.height, opts.boids, terrain);
+    world.add_random_predators(opts.predators);
+    if let Some(k) = opts.topological {
+        world.neighbour_mode = NeighbourMode::Topological(k);
+    }
+    scenario.apply(&mut world);
+    world
+}
+
+/// Read a terrain map from a text file, or from a PNG when built with `render`
+fn load_terrain(path: &str, width: f32, height: f32) -> Result<TerrainMap, String> {
+    if path.to_ascii_lowercase().ends_with(".png") {
+        #[cfg(feature = "render")]
+        {
+            let image = load_png(Path::new(path)).map_err(|e| e.to_string())?;
+            return TerrainMap::from_rgba(image.width as usize, image.height as usize, &image.pixels, width, height)
+                .ok_or_else(|| "empty image".to_string());
+        }
+        #[cfg(not(feature = "render"))]
+        return Err("PNG terrain needs boids-cli built with `--features render`".into());
+    }
+    let text = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    TerrainMap::parse(&text, width, height)
+}
+
+fn main() {
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    let subcommand = args.first().map(String::as_str);
+    if subcommand == Some("sweep") {
+        args.remove(0);
+        exit_on_error(run_sweep(args));
+        return;
+    }
+    let view = subcommand == Some("view");
+    if view {
+        args.remove(0);
+    }
+
+    let opts = parse_args(args).unwrap_or_else(|e| {
+        eprintln!("error: {}\n\n{}", e, USAGE);
+        process::exit(2);
+    });
+    if view {
+        #[cfg(feature = "tui")]
+        {
+            exit_on_error(run_view(&opts, build_world(&opts)));
+            return;
+        }
+        #[cfg(not(feature = "tui"))]
+        {
+            eprintln!("error: view needs boids-cli built with `--features tui`");
+            process::exit(2);
+        }
+    }
+
+    let mut world = build_world(&opts);
+
+    let mut out = BufWriter::new(io::stdout().lock());
+    let emit = |out: &mut BufWriter<_>, world: &World| -> io::Result<()> {
+        let m = Metrics::from_world(world);
+        match opts.format {
+            Format::Csv => writeln!(out, "{}", m.to_csv()),
+            Format::Ndjson => writeln!(out, "{}", m.to_ndjson()),
+        }
+    };
+
+    let result = (|| -> io::Result<()> {
+        #[cfg(feature = "render")]
+        let mut frames = Frames::new(&opts, &world)?;
+
+        if opts.format == Format::Csv {
+            writeln!(out, "{}", Metrics::CSV_HEADER)?;
+        }
+        emit(&mut out, &world)?;
+        for t in 1..=opts.ticks {
+            world.tick(0.0, 0.0, 0, 0.0);
+            if t % opts.every == 0 || t == opts.ticks {
+                emit(&mut out, &world)?;
+            }
+            #[cfg(feature = "render")]
+            if t % opts.frame_every == 0 {
+                frames.write(&world)?;
+            }
+        }
+        if let Some(path) = &opts.svg {
+            let options = SvgOptions { velocity_vectors: false, neighbour_links: true };
+            std::fs::write(path, world.export_svg(options))?;
+        }
+        out.flush()
+    })();
+
+    exit_on_error(result);
+}
+
+/// PNG and/or GIF frame output for a run
+#[cfg(feature = "render")]
+struct Frames {
+    canvas: Canvas,
+    png_dir: Option<PathBuf>,
+    gif: Option<GifWriter>,
+    index: u32,
+}
+
+#[cfg(feature = "render")]
+impl Frames {
+    fn new(opts: &Options, world: &World) -> io::Result<Self> {
+        let (w, h) = (world.width.round() as u32, world.height.round() as u32);
+        let png_dir = opts.png_dir.as_ref().map(PathBuf::from);
+        if let Some(dir) = &png_dir {
+            std::fs::create_dir_all(dir)?;
+        }
+        // GIF delays are in hundredths of a second; one tick is 16ms
+        let delay = ((opts.frame_every as f32 * STEP_MS) / 10.0).round().max(2.0) as u16;
+        let gif = match &opts.gif {
+            Some(path) => Some(GifWriter::create(Path::new(path), w, h, delay)?),
+            None => None,
+        };
+        Ok(Self { canvas: Canvas::new(w, h), png_dir, gif, index: 0 })
+    }
+
+    fn write(&mut self, world: &World) -> io::Result<()> {
+        if self.png_dir.is_none() && self.gif.is_none() { return Ok(()); }
+        draw_world(world, &mut self.canvas);
+        if let Some(dir) = &self.png_dir {
+            save_png(&self.canvas, &dir.join(format!("frame_{:05}.png", self.index)))?;
+        }
+        if let Some(gif) = &mut self.gif {
+            gif.add_frame(&self.canvas)?;
+        }
+        self.index += 1;
+        Ok(())
+    }
+}
+
+/// Raw mode and the alternate screen for the lifetime of the viewer
+#[cfg(feature = "tui")]
+struct TermGuard;
+
+#[cfg(feature = "tui")]
+impl TermGuard {
+    fn enter() -> io::Result<Self> {
+        use crossterm::{cursor, execute, terminal};
+        terminal::enable_raw_mode()?;
+        execute!(io::stdout(), terminal::EnterAlternateScreen, cursor::Hide, terminal::Clear(terminal::ClearType::All))?;
+        Ok(TermGuard)
+    }
+}
+
+#[cfg(feature = "tui")]
+impl Drop for TermGuard {
+    fn drop(&mut self) {
+        use crossterm::{cursor, execute, terminal};
+        let _ = execute!(io::stdout(), cursor::Show, terminal::LeaveAlternateScreen);
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+/// Live terminal view: redraws every `frame_every` ticks until `q`
+#[cfg(feature = "tui")]
+fn run_view(opts: &Options, mut world: World) -> io::Result<()> {
+    use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+    use std::time::{Duration, Instant};
+
+    let _guard = TermGuard::enter()?;
+    let mut stdout = io::stdout();
+    let mut canvas = TermCanvas::new(1, 1);
+    let mut frame = Vec::new();
+    let mut paused = false;
+    // Toggling compares the two neighbour modes on the same flock
+    let topological = NeighbourMode::Topological(opts.topological.unwrap_or(TOPOLOGICAL_K));
+    let frame_time = Duration::from_millis((opts.frame_every as f32 * STEP_MS) as u64);
+
+    loop {
+        // Follow terminal resizes; the last row is the status line
+        let (cols, rows) = crossterm::terminal::size()?;
+        let rows = rows.saturating_sub(1);
+        if (canvas.cols, canvas.rows) != (cols.max(1), rows.max(1)) {
+            canvas = TermCanvas::new(cols, rows);
+        }
+        canvas.draw_world(&world);
+
+        frame.clear();
+        canvas.write_ansi(&mut frame)?;
+        let m = Metrics::from_world(&world);
+        let mode = match world.neighbour_mode {
+            NeighbourMode::Metric => "metric".to_string(),
+            NeighbourMode::Topological(k) => format!("k={}", k),
+        };
+        write!(frame, "\r\n\x1b[2Ktick {}  boids {}  predators {}  bugs {}  {} {}  {}  {}  [space] pause [s] step [p] predator [t] mode [q] quit",
+            m.tick, m.boids, m.predators, m.bugs, world.season().name(), m.weather.name(), mode, if paused { "PAUSED" } else { "" })?;
+        stdout.write_all(&frame)?;
+        stdout.flush()?;
+
+        // Handle input until the next frame is due
+        let deadline = Instant::now() + frame_time;
+        let mut step = false;
+        while let Some(wait) = deadline.checked_duration_since(Instant::now()) {
+            if !event::poll(wait)? { break; }
+            match event::read()? {
+                Event::Key(key) if key.kind == KeyEventKind::Press => match key.code {
+                    KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+                    KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => return Ok(()),
+                    KeyCode::Char(' ') => paused = !paused,
+                    KeyCode::Char('s') => {
+                        paused = true;
+                        step = true;
+                    }
+                    KeyCode::Char('p') => world.add_predator(world.width / 2.0, world.height / 2.0),
+                    KeyCode::Char('t') => {
+                        world.neighbour_mode = if world.neighbour_mode == NeighbourMode::Metric {
+                            topological
+                        } else {
+                            NeighbourMode::Metric
+                        };
+                    }
+                    _ => continue,
+                },
+                Event::Resize(..) => {}
+                _ => continue,
+            }
+            // Redraw straight away so input feels immediate
+            break;
+        }
+
+        if step {
+            world.tick(0.0, 0.0, 0, 0.0);
+        } else if !paused && Instant::now() >= deadline {
+            for _ in 0..opts.frame_every {
+                world.tick(0.0, 0.0, 0, 0.0);
+            }
+        }
+    }
+}
+
+fn exit_on_error(result: io::Result<()>) {
+    // A closed pipe (e.g. `| head`) is not an error worth reporting
+    if let Err(e) = result {
+        if e.kind() != io::ErrorKind::BrokenPipe {
+            eprintln!("error: {}", e);
+            process::exit(1);
+        }
+    }
+}
@@ -1,7 +1,13 @@
 use crate::vector::Vec2;
+use crate::obstacle::Obstacles;
+use crate::flow::FlowField;
+use crate::weather::Weather;
+use crate::terrain::GROUND_DEPTH;
 
 const MAX_SPEED: f32 = 4.0;
-const MAX_FORCE: f32 = 0.15;
+const BODY_RADIUS: f32 = 5.0;
+/// Least speed a storm-grounded boid sinks at on its way down
+const DESCENT_SPEED: f32 = 1.5;
 
 /// Mutation flags stored as bitfields for efficiency
 #[derive(Clone, Copy, Default)]
@@ -61,7 +67,7 @@ impl Mutations {
     }
 }
 
-#[derive(Clone, Copy, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum BoidState {
     Normal,
     Perching,
@@ -72,6 +78,8 @@ pub enum BoidState {
 #[derive(Clone)]
 pub struct Boid {
     pub position: Vec2,
+    /// Position before the last update, for render interpolation
+    pub prev_position: Vec2,
     pub velocity: Vec2,
     pub acceleration: Vec2,
     pub species: u8,
@@ -96,6 +104,8 @@ pub struct Boid {
     // Computed from mutations
     pub size_mult: f32,
     pub speed_mult: f32,
+    /// How strongly wind moves the boid
+    pub wind_mult: f32,
     pub fatigue_resistance: f32,
     pub energy_drain: f32,
 }
@@ -113,6 +123,7 @@ impl Boid {
 
         let mut boid = Self {
             position: Vec2::new(x, y),
+            prev_position: Vec2::new(x, y),
             velocity: Vec2::from_angle(angle, 2.0 + rand_f32() * 2.0),
             acceleration: Vec2::zero(),
             species,
@@ -135,6 +146,7 @@ impl Boid {
             sociability: rand_f32(),
             size_mult: 1.0,
             speed_mult: 1.0,
+            wind_mult: 1.0,
             fatigue_resistance: 1.0,
             energy_drain: 1.0,
         };
@@ -156,6 +168,10 @@ impl Boid {
         if self.mutations.has(Mutations::FLIGHTLESS) { self.speed_mult *= 0.5; }
         if self.mutations.has(Mutations::MECHANICAL) { self.speed_mult *= 1.1; }
 
+        // Small birds catch the wind; paper ones go where it takes them
+        self.wind_mult = 1.0 / self.size_mult;
+        if self.mutations.has(Mutations::PAPER) { self.wind_mult *= 2.5; }
+
         self.fatigue_resistance = if self.mutations.has(Mutations::TOUGH) {
             0.5
         } else if self.mutations.has(Mutations::MECHANICAL) {
@@ -198,206 +214,27 @@ impl Boid {
         }
     }
 
-    pub fn flock(
-        &mut self,
-        nearby_boids: &[&Boid],
-        predator_positions: &[(f32, f32)],
-        obstacle_positions: &[(f32, f32)],
-        shelter_positions: &[(f32, f32, f32)], // x, y, radius
-        day_phase: f32,
-    ) {
-        if self.state != BoidState::Normal { return; }
-        if self.fatigue > 150.0 {
-            self.state = BoidState::Collapsed;
-            self.collapse_timer = 100;
-            self.velocity.reset();
-            return;
-        }
-
-        let px = self.position.x;
-        let py = self.position.y;
-        let vx = self.velocity.x;
-        let vy = self.velocity.y;
-
-        // Separation, alignment, cohesion
-        let mut sep_x = 0.0f32;
-        let mut sep_y = 0.0f32;
-        let mut ali_x = 0.0f32;
-        let mut ali_y = 0.0f32;
-        let mut coh_x = 0.0f32;
-        let mut coh_y = 0.0f32;
-        let mut sep_ct = 0u32;
-        let mut ali_ct = 0u32;
-        let mut coh_ct = 0u32;
-
-        let coh_radius = 50.0 * (1.0 + (1.0 - day_phase) * 0.5 + self.sociability * 0.3);
-        let coh_radius_sq = coh_radius * coh_radius;
-
-        for other in nearby_boids {
-            if std::ptr::eq(*other, self) || other.state != BoidState::Normal { continue; }
-
-            let ox = other.position.x;
-            let oy = other.position.y;
-            let dx = px - ox;
-            let dy = py - oy;
-            let dsq = dx * dx + dy * dy;
-
-            if dsq < 625.0 {
-                let inv = 1.0 / (dsq + 0.001);
-                sep_x += dx * inv;
-                sep_y += dy * inv;
-                sep_ct += 1;
-            }
-
-            let can_flock = self.is_hybrid || other.is_hybrid || other.species == self.species;
-            if can_flock {
-                if dsq < 2500.0 {
-                    ali_x += other.velocity.x;
-                    ali_y += other.velocity.y;
-                    ali_ct += 1;
-                }
-                if dsq < coh_radius_sq {
-                    coh_x += ox;
-                    coh_y += oy;
-                    coh_ct += 1;
-                }
-            }
-        }
-
-        let mut ax = 0.0f32;
-        let mut ay = 0.0f32;
-
-        // Apply separation
-        if sep_ct > 0 {
-            let mut sx = sep_x / sep_ct as f32;
-            let mut sy = sep_y / sep_ct as f32;
-            let sm = (sx * sx + sy * sy).sqrt();
-            if sm > 0.0 { sx /= sm; sy /= sm; }
-            sx = sx * MAX_SPEED - vx;
-            sy = sy * MAX_SPEED - vy;
-            let sm = (sx * sx + sy * sy).sqrt();
-            if sm > MAX_FORCE { sx = sx / sm * MAX_FORCE; sy = sy / sm * MAX_FORCE; }
-            ax += sx * 1.8;
-            ay += sy * 1.8;
-        }
-
-        // Apply alignment
-        if ali_ct > 0 {
-            let mut lx = ali_x / ali_ct as f32;
-            let mut ly = ali_y / ali_ct as f32;
-            let lm = (lx * lx + ly * ly).sqrt();
-            if lm > 0.0 { lx /= lm; ly /= lm; }
-            lx = lx * MAX_SPEED - vx;
-            ly = ly * MAX_SPEED - vy;
-            let lm = (lx * lx + ly * ly).sqrt();
-            if lm > MAX_FORCE { lx = lx / lm * MAX_FORCE; ly = ly / lm * MAX_FORCE; }
-            ax += lx;
-            ay += ly;
-        }
-
-        // Apply cohesion
-        if coh_ct > 0 {
-            let mut cx = coh_x / coh_ct as f32 - px;
-            let mut cy = coh_y / coh_ct as f32 - py;
-            let cm = (cx * cx + cy * cy).sqrt();
-            if cm > 0.0 { cx /= cm; cy /= cm; }
-            cx = cx * MAX_SPEED - vx;
-            cy = cy * MAX_SPEED - vy;
-            let cm = (cx * cx + cy * cy).sqrt();
-            if cm > MAX_FORCE { cx = cx / cm * MAX_FORCE; cy = cy / cm * MAX_FORCE; }
-            let sm = 1.0 + self.sociability * 0.3;
-            ax += cx * sm;
-            ay += cy * sm;
-        }
-
-        // Flee from predators
-        let mut flee_x = 0.0f32;
-        let mut flee_y = 0.0f32;
-        self.fear = if self.mutations.has(Mutations::BULLIED) { 0.3 } else { 0.0 };
-        let flee_radius = 100.0 + (1.0 - self.bravery) * 50.0;
-        let flee_radius_sq = flee_radius * flee_radius;
-
-        // Check if in shelter
-        let mut in_shelter = false;
-        for &(sx, sy, sr) in shelter_positions {
-            let dx = sx - px;
-            let dy = sy - py;
-            if dx * dx + dy * dy < sr * sr {
-                in_shelter = true;
-                self.fear = if self.mutations.has(Mutations::BULLIED) { 0.2 } else { 0.0 };
-                break;
-            }
-        }
-
-        if !in_shelter {
-            for &(pred_x, pred_y) in predator_positions {
-                let dx = px - pred_x;
-                let dy = py - pred_y;
-                let dsq = dx * dx + dy * dy;
-                if dsq < flee_radius_sq {
-                    let d = dsq.sqrt();
-                    let inv = 1.0 / (d + 0.001);
-                    flee_x += dx * inv;
-                    flee_y += dy * inv;
-                    self.fear = self.fear.max(1.0 - d / flee_radius);
-                }
-            }
-        }
-
-        if flee_x != 0.0 || flee_y != 0.0 {
-            let fm = (flee_x * flee_x + flee_y * flee_y).sqrt();
-            if fm > 0.0 { flee_x /= fm; flee_y /= fm; }
-            let fs = MAX_SPEED * (1.5 + self.bravery * 0.3);
-            flee_x = flee_x * fs - vx;
-            flee_y = flee_y * fs - vy;
-            let fm = (flee_x * flee_x + flee_y * flee_y).sqrt();
-            let fl = MAX_FORCE * 3.0;
-            if fm > fl { flee_x = flee_x / fm * fl; flee_y = flee_y / fm * fl; }
-            let flee_mult = if self.fear > 0.3 { 4.0 } else { 1.0 } * (4.0 - self.bravery);
-            ax += flee_x * flee_mult;
-            ay += flee_y * flee_mult;
-        }
-
-        // Avoid obstacles
-        let mut obs_x = 0.0f32;
-        let mut obs_y = 0.0f32;
-        let obs_radius_sq = if self.fear > 0.3 { 6400.0 } else { 2500.0 };
-
-        for &(ox, oy) in obstacle_positions {
-            let dx = px - ox;
-            let dy = py - oy;
-            let dsq = dx * dx + dy * dy;
-            if dsq < obs_radius_sq {
-                let inv = 1.0 / (dsq + 0.001);
-                obs_x += dx * inv;
-                obs_y += dy * inv;
-            }
-        }
-
-        if obs_x != 0.0 || obs_y != 0.0 {
-            let om = (obs_x * obs_x + obs_y * obs_y).sqrt();
-            if om > 0.0 { obs_x /= om; obs_y /= om; }
-            let os = MAX_SPEED * if self.fear > 0.3 { 1.5 } else { 1.0 };
-            obs_x = obs_x * os - vx;
-            obs_y = obs_y * os - vy;
-            let om = (obs_x * obs_x + obs_y * obs_y).sqrt();
-            let ol = MAX_FORCE * if self.fear > 0.3 { 3.0 } else { 2.0 };
-            if om > ol { obs_x = obs_x / om * ol; obs_y = obs_y / om * ol; }
-            let obs_mult = if self.fear > 0.3 { 3.0 } else { 1.0 };
-            ax += obs_x * obs_mult;
-            ay += obs_y * obs_mult;
-        }
-
-        self.acceleration.x += ax;
-        self.acceleration.y += ay;
+    /// Flightless and paper boids cannot fly in a storm. They drop to the
+    /// strip along the bottom of the world and sit it out there.
+    pub fn grounded_by(&self, weather: Weather) -> bool {
+        weather == Weather::Storm && (self.mutations.has(Mutations::FLIGHTLESS) || self.mutations.has(Mutations::PAPER))
     }
 
-    pub fn update(&mut self, width: f32, height: f32, day_phase: f32) -> bool {
+    /// Step one tick. `light` is the world's light level (0 dark, 1 noon).
+    pub fn update(&mut self, width: f32, height: f32, light: f32, weather: Weather, obstacles: &Obstacles, flow: &FlowField) -> bool {
+        self.prev_position = self.position;
         self.age += 1;
         if self.age > self.max_age && !self.mutations.has(Mutations::IMMORTAL) {
             return false;
         }
 
+        let grounded = self.grounded_by(weather);
+        let landed = self.position.y >= height - GROUND_DEPTH;
+        if grounded && landed && self.state == BoidState::Normal {
+            self.state = BoidState::Perching;
+            self.velocity = Vec2::zero();
+        }
+
         match self.state {
             BoidState::Fishing => {
                 self.fish_timer = self.fish_timer.saturating_sub(1);
@@ -409,7 +246,7 @@ impl Boid {
             BoidState::Perching => {
                 self.fatigue = (self.fatigue - 0.8).max(0.0);
                 self.energy -= 0.003 * self.energy_drain;
-                if (day_phase > 0.5 && self.fatigue < 20.0) || self.fear > 0.5 {
+                if !grounded && ((light > 0.5 && self.fatigue < 20.0) || self.fear > 0.5) {
                     self.state = BoidState::Normal;
                     let angle = rand_f32() * std::f32::consts::TAU;
                     self.velocity = Vec2::from_angle(angle, 2.0);
@@ -429,29 +266,36 @@ impl Boid {
         }
 
         // Energy and fatigue
-        self.energy -= (0.012 + self.hunger * 0.005) * self.energy_drain;
-        self.fatigue += (0.025 - self.laziness * 0.01) * self.fatigue_resistance;
+        self.energy -= (0.012 + self.hunger * 0.005) * self.energy_drain * weather.energy_mult();
+        self.fatigue += (0.025 - self.laziness * 0.01) * self.fatigue_resistance * weather.fatigue_mult();
 
         if self.fear > 0.0 {
             self.energy -= 0.04 * self.energy_drain;
             self.fatigue += 0.04 * self.fatigue_resistance;
         }
 
-        if self.mutations.has(Mutations::NOCTURNAL) && day_phase < 0.4 {
+        if self.mutations.has(Mutations::NOCTURNAL) && light < 0.4 {
             self.energy += 0.005;
             self.fatigue -= 0.01;
         }
 
         // Physics
         let age_slow = (1.0 - (self.age as f32 / self.max_age as f32) * 0.5).max(0.5);
-        let speed_mult = (0.6 + day_phase * 0.4) * age_slow * self.speed_mult;
-        let nocturnal_bonus = if self.mutations.has(Mutations::NOCTURNAL) && day_phase < 0.4 { 1.3 } else { 1.0 };
+        let speed_mult = (0.6 + light * 0.4) * age_slow * self.speed_mult;
+        let nocturnal_bonus = if self.mutations.has(Mutations::NOCTURNAL) && light < 0.4 { 1.3 } else { 1.0 };
         let current_max_speed = MAX_SPEED * speed_mult * (1.0 + self.fear * 0.5) * nocturnal_bonus;
 
+        if !flow.is_calm() {
+            self.acceleration.add_mut(flow.sample(self.position.x, self.position.y).mult(self.wind_mult));
+        }
         self.velocity.add_mut(self.acceleration);
         self.velocity.limit_mut(current_max_speed);
+        if grounded {
+            self.velocity.y = self.velocity.y.max(DESCENT_SPEED);
+        }
         self.position.add_mut(self.velocity);
         self.acceleration.reset();
+        obstacles.collide(&mut self.position, &mut self.velocity, BODY_RADIUS * self.size_mult);
 
         // Wrap edges
         if self.position.x > width { self.position.x = 0.0; }
@@ -463,9 +307,10 @@ impl Boid {
     }
 }
 
+static mut SEED: u32 = 12345;
+
 // Simple random number generator (xorshift)
 fn rand_f32() -> f32 {
-    static mut SEED: u32 = 12345;
     unsafe {
         SEED ^= SEED << 13;
         SEED ^= SEED >> 17;
@@ -476,7 +321,52 @@ fn rand_f32() -> f32 {
 
 pub fn set_seed(seed: u32) {
     unsafe {
-        static mut SEED: u32 = 12345;
-        SEED = seed;
+        SEED = if seed == 0 { 12345 } else { seed };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn storm_grounded_boids_come_down_before_perching() {
+        let _rng = crate::rng_lock();
+        let (width, height) = (900.0, 600.0);
+        let (obstacles, flow) = (Obstacles::new(), FlowField::new());
+        for flag in [Mutations::FLIGHTLESS, Mutations::PAPER] {
+            let mut boid = Boid::new(450.0, 100.0, 0, false);
+            boid.mutations.set(flag);
+            boid.apply_mutation_effects();
+            boid.velocity = Vec2::new(1.0, -2.0);
+            boid.energy = 100.0;
+            assert!(boid.grounded_by(Weather::Storm) && !boid.grounded_by(Weather::Rain));
+
+            let mut ticks = 0;
+            while boid.state == BoidState::Normal {
+                let y = boid.position.y;
+                assert!(boid.update(width, height, 1.0, Weather::Storm, &obstacles, &flow));
+                assert!(boid.position.y >= y, "rose at tick {ticks}");
+                ticks += 1;
+                assert!(ticks < 400, "never came down");
+            }
+            assert_eq!(boid.state, BoidState::Perching);
+            assert!(boid.position.y >= height - GROUND_DEPTH);
+
+            let at = boid.position;
+            for _ in 0..50 {
+                boid.update(width, height, 1.0, Weather::Storm, &obstacles, &flow);
+            }
+            assert_eq!(boid.state, BoidState::Perching);
+            assert_eq!((boid.position.x, boid.position.y), (at.x, at.y));
+
+            boid.update(width, height, 1.0, Weather::Clear, &obstacles, &flow);
+            assert_eq!(boid.state, BoidState::Normal);
+        }
+
+        // Boids that fly on through storms never land
+        let mut boid = Boid::new(450.0, 590.0, 0, false);
+        boid.update(width, height, 1.0, Weather::Storm, &obstacles, &flow);
+        assert_eq!(boid.state, BoidState::Normal);
     }
 }
@@ -1,61 +1,123 @@
 use crate::vector::Vec2;
+use crate::behaviour::MAX_FORCE;
+use crate::obstacle::{Obstacles, AVOID_MARGIN};
+use crate::flow::FlowField;
+
+/// Bugs are light and drift with the air
+const WIND_MULT: f32 = 2.0;
+/// Ticks between a lone firefly's flashes
+const FLASH_PERIOD: f32 = 90.0;
+/// Fireflies within this distance see each other's flashes
+const SYNC_RADIUS: f32 = 120.0;
+/// How far one flash pushes a neighbour's clock on, as a share of where it is
+const SYNC_COUPLING: f32 = 0.3;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BugKind {
+    /// Flies about by day and night
+    #[default]
+    Common,
+    /// Comes out after dark and flashes in step with its neighbours
+    Firefly,
+}
 
 #[derive(Clone)]
 pub struct Bug {
+    pub kind: BugKind,
     pub position: Vec2,
+    /// Position before the last update, for render interpolation
+    pub prev_position: Vec2,
     pub velocity: Vec2,
     pub energy: f32,
     pub size: f32,
     pub hue: f32,
     pub lifetime: u32,
+    /// Flash clock, 0..1; a firefly flashes when it reaches 1
+    pub phase: f32,
+    /// Glow, 1 at a flash fading toward 0. Always 0 for common bugs.
+    pub brightness: f32,
+    /// Flashed this tick
+    flashed: bool,
 }
 
 impl Bug {
     pub fn new(x: f32, y: f32) -> Self {
         let angle = rand_f32() * std::f32::consts::TAU;
         Self {
+            kind: BugKind::Common,
             position: Vec2::new(x, y),
+            prev_position: Vec2::new(x, y),
             velocity: Vec2::from_angle(angle, 1.0 + rand_f32()),
             energy: 20.0,
             size: 2.0 + rand_f32() * 2.0,
             hue: if rand_f32() > 0.5 { 60.0 } else { 120.0 },
             lifetime: 500 + (rand_f32() * 500.0) as u32,
+            phase: 0.0,
+            brightness: 0.0,
+            flashed: false,
         }
     }
 
-    pub fn update(&mut self, width: f32, height: f32, obstacle_positions: &[(f32, f32)]) -> bool {
+    /// A firefly at a random point in its flash cycle. Slower and longer
+    /// lived than a common bug.
+    pub fn firefly(x: f32, y: f32) -> Self {
+        let angle = rand_f32() * std::f32::consts::TAU;
+        Self {
+            kind: BugKind::Firefly,
+            position: Vec2::new(x, y),
+            prev_position: Vec2::new(x, y),
+            velocity: Vec2::from_angle(angle, 0.5 + rand_f32() * 0.5),
+            energy: 20.0,
+            size: 2.5,
+            hue: 75.0,
+            lifetime: 1200 + (rand_f32() * 800.0) as u32,
+            phase: rand_f32(),
+            brightness: 0.0,
+            flashed: false,
+        }
+    }
+
+    pub fn update(&mut self, width: f32, height: f32, obstacles: &Obstacles, flow: &FlowField) -> bool {
+        self.prev_position = self.position;
         self.lifetime = self.lifetime.saturating_sub(1);
         if self.lifetime == 0 || self.energy <= 0.0 {
             return false;
         }
 
+        let max_speed = match self.kind {
+            BugKind::Common => 2.0,
+            BugKind::Firefly => {
+                self.brightness *= 0.85;
+                self.phase += 1.0 / FLASH_PERIOD;
+                self.flashed = self.phase >= 1.0;
+                if self.flashed {
+                    self.phase = 0.0;
+                    self.brightness = 1.0;
+                }
+                1.0
+            }
+        };
+
         // Random steering
         let angle = rand_f32() * std::f32::consts::TAU;
         let steer = Vec2::from_angle(angle, 0.15);
         self.velocity.add_mut(steer);
-        self.velocity.limit_mut(2.0);
 
         // Avoid obstacles
-        let px = self.position.x;
-        let py = self.position.y;
-        let next_x = px + self.velocity.x;
-        let next_y = py + self.velocity.y;
-
-        for &(ox, oy) in obstacle_positions {
-            let dx = next_x - ox;
-            let dy = next_y - oy;
-            if dx * dx + dy * dy < 18.0 * 18.0 {
-                let dx = px - ox;
-                let dy = py - oy;
-                let m = (dx * dx + dy * dy).sqrt();
-                if m > 0.0 {
-                    self.velocity.set(dx / m * 2.0, dy / m * 2.0);
-                }
-                break;
-            }
+        let (ox, oy) = obstacles.avoidance(self.position.x, self.position.y, self.velocity, AVOID_MARGIN);
+        let om = (ox * ox + oy * oy).sqrt();
+        if om > 0.0 {
+            self.velocity.x += ox / om * MAX_FORCE * 3.0;
+            self.velocity.y += oy / om * MAX_FORCE * 3.0;
+        }
+
+        if !flow.is_calm() {
+            self.velocity.add_mut(flow.sample(self.position.x, self.position.y).mult(WIND_MULT));
         }
+        self.velocity.limit_mut(max_speed);
 
         self.position.add_mut(self.velocity);
+        obstacles.collide(&mut self.position, &mut self.velocity, self.size);
 
         // Wrap edges
         if self.position.x < 0.0 { self.position.x = width; }
@@ -67,8 +129,36 @@ impl Bug {
     }
 }
 
+/// Pulse coupling between fireflies: every flash pushes the clocks of
+/// fireflies that see it forward in proportion to how far along they are.
+/// Any pushed to the end of their cycle flash at once, which can set off
+/// their own neighbours in turn, so clusters fall into step within a few
+/// cycles (Mirollo and Strogatz). Call once per tick after `update`.
+pub fn sync_fireflies(bugs: &mut [Bug]) {
+    let mut flashes: Vec<Vec2> = bugs.iter().filter(|b| b.flashed).map(|b| b.position).collect();
+    let mut seen_from = 0;
+    while seen_from < flashes.len() {
+        let new = &flashes[seen_from..];
+        let mut fired = Vec::new();
+        for bug in bugs.iter_mut().filter(|b| b.kind == BugKind::Firefly && !b.flashed) {
+            let seen = new.iter().filter(|p| p.dist_sq(bug.position) < SYNC_RADIUS * SYNC_RADIUS).count();
+            if seen == 0 { continue; }
+            bug.phase *= (1.0 + SYNC_COUPLING).powi(seen as i32);
+            if bug.phase >= 1.0 {
+                bug.phase = 0.0;
+                bug.brightness = 1.0;
+                bug.flashed = true;
+                fired.push(bug.position);
+            }
+        }
+        seen_from = flashes.len();
+        flashes.extend(fired);
+    }
+}
+
+static mut SEED: u32 = 11111;
+
 fn rand_f32() -> f32 {
-    static mut SEED: u32 = 11111;
     unsafe {
         SEED ^= SEED << 13;
         SEED ^= SEED >> 17;
@@ -76,3 +166,71 @@ fn rand_f32() -> f32 {
         (SEED as f32) / (u32::MAX as f32)
     }
 }
+
+pub fn set_seed(seed: u32) {
+    unsafe {
+        SEED = if seed == 0 { 11111 } else { seed };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Run a pair of fireflies, held in place, for `ticks` ticks and return
+    /// the ticks each flashed on
+    fn flashes(a: Vec2, b: Vec2, phases: (f32, f32), ticks: u32) -> [Vec<u32>; 2] {
+        let (obstacles, flow) = (Obstacles::new(), FlowField::new());
+        let mut bugs = vec![Bug::firefly(a.x, a.y), Bug::firefly(b.x, b.y)];
+        (bugs[0].phase, bugs[1].phase) = phases;
+        let mut seen = [Vec::new(), Vec::new()];
+        for t in 0..ticks {
+            for (bug, at) in bugs.iter_mut().zip([a, b]) {
+                assert!(bug.update(1000.0, 1000.0, &obstacles, &flow));
+                (bug.position, bug.velocity) = (at, Vec2::zero());
+            }
+            sync_fireflies(&mut bugs);
+            for (seen, bug) in seen.iter_mut().zip(&bugs) {
+                if bug.flashed {
+                    seen.push(t);
+                }
+            }
+        }
+        seen
+    }
+
+    #[test]
+    fn neighbouring_fireflies_fall_into_step() {
+        let _rng = crate::rng_lock();
+        let period = FLASH_PERIOD as u32;
+        let a = Vec2::new(500.0, 500.0);
+        let [first, second] = flashes(a, a.add(Vec2::new(SYNC_RADIUS * 0.8, 0.0)), (0.1, 0.6), period * 10);
+
+        let together = first.iter().position(|t| second.contains(t)).expect("never flashed together");
+        assert!(first[together] < period * 5, "took until tick {}", first[together]);
+        // Once in step they stay there, flashing once a period
+        assert_eq!(first[together..], second[second.len() - (first.len() - together)..]);
+        for pair in first[together..].windows(2) {
+            assert!(pair[1] - pair[0] >= period - 1 && pair[1] - pair[0] <= period + 1);
+        }
+    }
+
+    #[test]
+    fn fireflies_out_of_sight_keep_their_own_time() {
+        let _rng = crate::rng_lock();
+        let period = FLASH_PERIOD as u32;
+        let a = Vec2::new(200.0, 500.0);
+        let [first, second] = flashes(a, a.add(Vec2::new(SYNC_RADIUS * 1.5, 0.0)), (0.1, 0.6), period * 10);
+
+        assert!(first.iter().all(|t| !second.contains(t)));
+        for ticks in [&first, &second] {
+            assert!(ticks.len() >= 9);
+            for pair in ticks.windows(2) {
+                assert!(pair[1] - pair[0] >= period - 1 && pair[1] - pair[0] <= period + 1);
+            }
+        }
+        // Still half a cycle apart
+        let offset = (second[0] as i32 - first[0] as i32).abs();
+        assert!((offset - period as i32 / 2).abs() <= 1, "offset {offset}");
+    }
+}
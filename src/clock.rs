@@ -0,0 +1,114 @@
+/// Length of one simulation step in milliseconds. `World::tick` always
+/// advances by exactly this much.
+pub const STEP_MS: f32 = 16.0;
+/// Most steps run for a single `advance` call. Anything beyond this is
+/// dropped so a slow frame can't snowball into ever longer catch-up frames.
+pub const MAX_STEPS: u32 = 8;
+
+/// Fixed-timestep accumulator that turns real frame time into whole
+/// simulation steps, scaled by `game_speed`.
+#[derive(Clone, Copy)]
+pub struct FixedStep {
+    pub game_speed: f32,
+    pub max_steps: u32,
+    accumulator: f32,
+}
+
+impl FixedStep {
+    pub fn new() -> Self {
+        Self {
+            game_speed: 1.0,
+            max_steps: MAX_STEPS,
+            accumulator: 0.0,
+        }
+    }
+
+    /// Add `dt_ms` of real time and return how many steps to run now
+    pub fn consume(&mut self, dt_ms: f32) -> u32 {
+        if dt_ms.is_finite() && dt_ms > 0.0 {
+            self.accumulator += dt_ms * self.game_speed.max(0.0);
+        }
+
+        let due = (self.accumulator / STEP_MS) as u32;
+        let steps = due.min(self.max_steps);
+        self.accumulator -= steps as f32 * STEP_MS;
+        if due > steps {
+            // Spiral-of-death guard: keep only the partial step
+            self.accumulator %= STEP_MS;
+        }
+        steps
+    }
+
+    /// How far (0..1) real time has progressed into the next step.
+    /// Renderers interpolate between the previous and current step with this.
+    #[inline]
+    pub fn alpha(&self) -> f32 {
+        (self.accumulator / STEP_MS).clamp(0.0, 1.0)
+    }
+
+    pub fn reset(&mut self) {
+        self.accumulator = 0.0;
+    }
+}
+
+impl Default for FixedStep {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn half_speed_runs_half_the_steps() {
+        let mut clock = FixedStep::new();
+        clock.game_speed = 0.5;
+        let steps: u32 = (0..10).map(|_| clock.consume(16.0)).sum();
+        assert_eq!(steps, 5);
+        assert_eq!(clock.alpha(), 0.0);
+    }
+
+    #[test]
+    fn long_frame_is_capped_and_backlog_dropped() {
+        let mut clock = FixedStep::new();
+        assert_eq!(clock.consume(10_000.0), MAX_STEPS);
+        assert_eq!(clock.alpha(), 0.0);
+        assert_eq!(clock.consume(0.0), 0);
+    }
+
+    #[test]
+    fn zero_speed_and_bad_frames_do_not_advance() {
+        let mut clock = FixedStep::new();
+        clock.game_speed = 0.0;
+        assert_eq!(clock.consume(1000.0), 0);
+        clock.game_speed = 1.0;
+        for dt in [-16.0, f32::NAN, f32::INFINITY] {
+            assert_eq!(clock.consume(dt), 0);
+        }
+        assert_eq!(clock.alpha(), 0.0);
+    }
+
+    #[test]
+    fn alpha_is_the_fraction_of_the_next_step() {
+        let mut clock = FixedStep::new();
+        assert_eq!(clock.consume(20.0), 1);
+        assert_eq!(clock.alpha(), 0.25);
+        assert_eq!(clock.consume(20.0), 1);
+        assert_eq!(clock.alpha(), 0.5);
+    }
+
+    #[test]
+    fn reset_clears_the_partial_step_only() {
+        let mut clock = FixedStep::new();
+        clock.game_speed = 2.0;
+        clock.max_steps = 3;
+        clock.consume(12.0);
+        assert_eq!(clock.alpha(), 0.5);
+        clock.reset();
+        assert_eq!(clock.alpha(), 0.0);
+        assert_eq!((clock.game_speed, clock.max_steps), (2.0, 3));
+        assert_eq!(clock.consume(8.0), 1);
+    }
+}
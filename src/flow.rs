@@ -0,0 +1,226 @@
+//! Wind and other air currents acting on everything that flies.
+//!
+//! The field is the sum of a uniform wind, gusts from smooth value noise that
+//! drift as the seasons turn, and any vortices placed in the world. Sampling
+//! returns an acceleration; each entity scales it by how easily it is blown
+//! about.
+
+use crate::vector::Vec2;
+
+/// Gust pattern drift per unit of `season_time` (one lattice cell every
+/// ~100 ticks)
+const GUST_RATE: f32 = 50.0;
+
+/// A swirl of air. Positive strength turns clockwise on screen.
+#[derive(Clone, Copy)]
+pub struct Vortex {
+    pub position: Vec2,
+    pub radius: f32,
+    pub strength: f32,
+}
+
+pub struct FlowField {
+    /// Steady wind, as an acceleration per tick
+    pub wind: Vec2,
+    /// Peak gust acceleration; 0 disables gusts
+    pub gust_strength: f32,
+    /// Size of a gust in pixels
+    pub gust_scale: f32,
+    pub vortices: Vec<Vortex>,
+    /// Noise time, set from `season_time` each tick
+    phase: f32,
+}
+
+impl FlowField {
+    /// Still air
+    pub fn new() -> Self {
+        Self {
+            wind: Vec2::zero(),
+            gust_strength: 0.0,
+            gust_scale: 200.0,
+            vortices: Vec::new(),
+            phase: 0.0,
+        }
+    }
+
+    pub fn is_calm(&self) -> bool {
+        self.wind.x == 0.0 && self.wind.y == 0.0 && self.gust_strength == 0.0 && self.vortices.is_empty()
+    }
+
+    pub fn set_season_time(&mut self, season_time: f32) {
+        self.phase = season_time * GUST_RATE;
+    }
+
+    pub fn add_vortex(&mut self, x: f32, y: f32, radius: f32, strength: f32) {
+        self.vortices.push(Vortex { position: Vec2::new(x, y), radius: radius.max(1.0), strength });
+    }
+
+    /// Remove vortices centred within `dist` of the point
+    pub fn remove_vortex_near(&mut self, x: f32, y: f32, dist: f32) {
+        let p = Vec2::new(x, y);
+        self.vortices.retain(|v| v.position.dist_sq(p) > dist * dist);
+    }
+
+    /// Acceleration the air applies at a point
+    pub fn sample(&self, x: f32, y: f32) -> Vec2 {
+        let mut f = self.wind;
+
+        if self.gust_strength > 0.0 {
+            let (nx, ny) = (x / self.gust_scale, y / self.gust_scale);
+            // Two decorrelated noise channels for the two components
+            let gx = value_noise(nx, ny, self.phase) * 2.0 - 1.0;
+            let gy = value_noise(nx + 31.7, ny + 17.3, self.phase) * 2.0 - 1.0;
+            f.x += gx * self.gust_strength;
+            f.y += gy * self.gust_strength;
+        }
+
+        for v in &self.vortices {
+            let dx = x - v.position.x;
+            let dy = y - v.position.y;
+            let d = (dx * dx + dy * dy).sqrt();
+            if d < v.radius && d > 0.0 {
+                // Tangential, strongest near the centre and fading to the rim
+                let s = v.strength * (1.0 - d / v.radius) / d;
+                f.x -= dy * s;
+                f.y += dx * s;
+            }
+        }
+        f
+    }
+
+    /// Sample on a grid of `spacing` px cell centres as flat
+    /// `[x, y, fx, fy, ...]`, for drawing arrows
+    pub fn sample_grid(&self, width: f32, height: f32, spacing: f32) -> Vec<f32> {
+        let spacing = spacing.max(4.0);
+        let cols = (width / spacing).floor().max(0.0) as usize;
+        let rows = (height / spacing).floor().max(0.0) as usize;
+        let mut data = Vec::with_capacity(cols * rows * 4);
+        for row in 0..rows {
+            for col in 0..cols {
+                let x = (col as f32 + 0.5) * spacing;
+                let y = (row as f32 + 0.5) * spacing;
+                let f = self.sample(x, y);
+                data.extend_from_slice(&[x, y, f.x, f.y]);
+            }
+        }
+        data
+    }
+}
+
+impl Default for FlowField {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Lattice hash to 0..1
+#[inline]
+fn lattice(x: i32, y: i32, z: i32) -> f32 {
+    let mut h = (x as u32).wrapping_mul(0x8da6_b343)
+        ^ (y as u32).wrapping_mul(0xd816_3841)
+        ^ (z as u32).wrapping_mul(0xcb1a_b31f);
+    h ^= h >> 13;
+    h = h.wrapping_mul(0x5bd1_e995);
+    h ^= h >> 15;
+    (h & 0x00ff_ffff) as f32 / 0x0100_0000 as f32
+}
+
+#[inline]
+fn smooth(t: f32) -> f32 {
+    t * t * (3.0 - 2.0 * t)
+}
+
+/// Trilinear value noise in 0..1, smooth in all three axes
+fn value_noise(x: f32, y: f32, z: f32) -> f32 {
+    let (x0, y0, z0) = (x.floor(), y.floor(), z.floor());
+    let (tx, ty, tz) = (smooth(x - x0), smooth(y - y0), smooth(z - z0));
+    let (ix, iy, iz) = (x0 as i32, y0 as i32, z0 as i32);
+    let lerp = |a: f32, b: f32, t: f32| a + (b - a) * t;
+    let plane = |z: i32| {
+        let top = lerp(lattice(ix, iy, z), lattice(ix + 1, iy, z), tx);
+        let bottom = lerp(lattice(ix, iy + 1, z), lattice(ix + 1, iy + 1, z), tx);
+        lerp(top, bottom, ty)
+    };
+    lerp(plane(iz), plane(iz + 1), tz)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn still_air_pushes_nothing() {
+        let flow = FlowField::new();
+        assert!(flow.is_calm());
+        for (x, y) in [(0.0, 0.0), (123.0, 456.0), (-50.0, 9000.0)] {
+            let f = flow.sample(x, y);
+            assert_eq!((f.x, f.y), (0.0, 0.0));
+        }
+        assert!(flow.sample_grid(200.0, 100.0, 25.0).chunks(4).all(|c| c[2] == 0.0 && c[3] == 0.0));
+    }
+
+    #[test]
+    fn vortices_swirl_around_their_centre() {
+        let mut flow = FlowField::new();
+        flow.add_vortex(100.0, 100.0, 50.0, 2.0);
+        assert!(!flow.is_calm());
+        for (dx, dy) in [(25.0, 0.0), (0.0, 25.0), (-15.0, 20.0), (10.0, -10.0)] {
+            let f = flow.sample(100.0 + dx, 100.0 + dy);
+            let d: f32 = (dx * dx + dy * dy).sqrt();
+            // Tangential, and clockwise on screen (y down): right of centre blows down
+            assert!((f.x * dx + f.y * dy).abs() < 1e-4, "({dx}, {dy}) not tangential");
+            assert!(dx * f.y - dy * f.x > 0.0, "({dx}, {dy}) turns the wrong way");
+            assert!((f.mag() - 2.0 * (1.0 - d / 50.0)).abs() < 1e-4);
+        }
+        let right = flow.sample(125.0, 100.0);
+        assert!(right.y > 0.0 && right.x.abs() < 1e-6);
+
+        // Still at the eye and beyond the rim
+        for (x, y) in [(100.0, 100.0), (151.0, 100.0), (100.0, 40.0)] {
+            let f = flow.sample(x, y);
+            assert_eq!((f.x, f.y), (0.0, 0.0));
+        }
+
+        flow.vortices[0].strength = -2.0;
+        assert!(flow.sample(125.0, 100.0).y < 0.0);
+        flow.remove_vortex_near(105.0, 95.0, 10.0);
+        assert!(flow.is_calm());
+    }
+
+    #[test]
+    fn gusts_stay_within_their_strength() {
+        let mut flow = FlowField::new();
+        flow.gust_strength = 0.2;
+        flow.set_season_time(0.37);
+        let mut moved = false;
+        for i in 0..200 {
+            let (x, y) = (i as f32 * 37.0, i as f32 * 13.0);
+            let f = flow.sample(x, y);
+            assert!(f.x.abs() <= 0.2 && f.y.abs() <= 0.2);
+            let g = flow.sample(x, y);
+            assert_eq!((f.x, f.y), (g.x, g.y));
+            moved |= f.mag_sq() > 0.0;
+        }
+        assert!(moved);
+    }
+
+    #[test]
+    fn grid_is_row_major_cell_centres() {
+        let mut flow = FlowField::new();
+        flow.wind = Vec2::new(0.1, -0.05);
+        flow.add_vortex(50.0, 30.0, 40.0, 1.0);
+        let data = flow.sample_grid(110.0, 70.0, 20.0);
+        // 5 whole columns by 3 whole rows, 4 floats each
+        assert_eq!(data.len(), 5 * 3 * 4);
+        for (i, cell) in data.chunks(4).enumerate() {
+            let (col, row) = (i % 5, i / 5);
+            assert_eq!((cell[0], cell[1]), (col as f32 * 20.0 + 10.0, row as f32 * 20.0 + 10.0));
+            let f = flow.sample(cell[0], cell[1]);
+            assert_eq!((cell[2], cell[3]), (f.x, f.y));
+        }
+
+        // Spacing is floored at 4px so a tiny one cannot blow up the grid
+        assert_eq!(flow.sample_grid(40.0, 8.0, 0.0).len(), 10 * 2 * 4);
+        assert!(flow.sample_grid(10.0, 10.0, 20.0).is_empty());
+    }
+}
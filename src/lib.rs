@@ -3,10 +3,58 @@ mod spatial;
 mod boid;
 mod predator;
 mod bug;
+mod clock;
+mod obstacle;
+mod vision;
+mod flow;
+mod weather;
+mod season;
+mod terrain;
+mod scent;
+mod territory;
+mod behaviour;
 mod world;
+mod metrics;
+mod scenario;
+mod sweep;
+mod color;
+mod svg;
+#[cfg(feature = "render")]
+mod render;
+#[cfg(feature = "tui")]
+mod term;
+#[cfg(feature = "simd")]
+mod simd;
+
+pub use behaviour::{
+    steer_towards, Agent, Alignment, AvoidObstacles, Behaviour, Cohesion, Entry, Environment, Field, Flee,
+    FollowScent, Neighbour, NeighbourMode, Neighbourhood, Pipeline, Separation, Sum, Territorial,
+    FLEE_RADIUS, MAX_FORCE, MAX_SPEED, PANIC_FEAR, TOPOLOGICAL_K,
+};
+pub use boid::{Boid, BoidState, Mutations};
+pub use bug::{Bug, BugKind};
+pub use clock::{FixedStep, STEP_MS};
+pub use flow::{FlowField, Vortex};
+pub use metrics::Metrics;
+pub use obstacle::{Obstacle, Obstacles, Polygon, Wall, AVOID_MARGIN};
+pub use predator::Predator;
+pub use scenario::Scenario;
+pub use scent::{Channel, ScentGrid};
+pub use season::{Season, SeasonParams, SeasonTable};
+pub use svg::SvgOptions;
+pub use sweep::{parse_values, Summary, Sweep};
+pub use terrain::{Spawn, TerrainMap, Zone};
+pub use territory::{Owner, TerritoryGrid};
+pub use vector::Vec2;
+pub use vision::Vision;
+pub use weather::{Weather, WeatherSystem};
+pub use world::{Cursor, DayEvent, World};
+#[cfg(feature = "render")]
+pub use render::{draw_world, load_png, save_png, Canvas, GifWriter};
+#[cfg(feature = "tui")]
+pub use term::TermCanvas;
 
 use wasm_bindgen::prelude::*;
-use world::World;
 
 #[cfg(feature = "console_error_panic_hook")]
 #[wasm_bindgen(start)]
@@ -36,11 +84,107 @@ impl WasmWorld {
         self.world.tick(cursor_x, cursor_y, cursor_mode, cursor_strength);
     }
 
+    /// Advance by `dt_ms` of real time using fixed 16ms steps scaled by game speed.
+    /// Returns the interpolation alpha (0..1) into the next step for smooth rendering.
+    pub fn advance(&mut self, dt_ms: f32) -> f32 {
+        self.world.advance(dt_ms)
+    }
+
+    /// Set the simulation speed multiplier used by `advance` (fractional values allowed)
+    pub fn set_game_speed(&mut self, speed: f32) {
+        self.world.clock.game_speed = speed.max(0.0);
+    }
+
+    /// Set cursor input used by `advance`
+    /// mode: 0 = none, 1 = attract, 2 = repel
+    pub fn set_cursor(&mut self, x: f32, y: f32, mode: i32, strength: f32) {
+        self.world.cursor = Cursor { x, y, mode, strength };
+    }
+
     /// Get simulation statistics
-    /// Returns: [boid_count, predator_count, bug_count, day_phase]
+    /// Returns: [boid_count, predator_count, bug_count, day_phase, weather, territory0, territory1]
+    /// weather: 0 = clear, 1 = rain, 2 = storm, 3 = snow
+    /// territory0/1: share of the world each species holds, 0..1
     pub fn get_stats(&self) -> Vec<f32> {
-        let (boids, predators, bugs, day_phase) = self.world.get_stats();
-        vec![boids as f32, predators as f32, bugs as f32, day_phase]
+        let (boids, predators, bugs, day_phase, weather) = self.world.get_stats();
+        vec![
+            boids as f32, predators as f32, bugs as f32, day_phase, weather as u32 as f32,
+            self.world.territory_share(0), self.world.territory_share(1),
+        ]
+    }
+
+    /// Daylight after cloud cover, 0 = dark, 1 = clear noon
+    pub fn get_light_level(&self) -> f32 {
+        self.world.light_level()
+    }
+
+    /// 0 = midnight, 0.25 = dawn, 0.5 = noon, 0.75 = dusk
+    pub fn get_time_of_day(&self) -> f32 {
+        self.world.time_of_day()
+    }
+
+    pub fn set_time_of_day(&mut self, fraction: f32) {
+        self.world.set_time_of_day(fraction);
+    }
+
+    /// Ticks in a full day and night (default about 4200), scaled by each
+    /// season's day_length
+    pub fn get_day_length(&self) -> f32 {
+        self.world.day_length()
+    }
+
+    pub fn set_day_length(&mut self, ticks: f32) {
+        self.world.set_day_length(ticks);
+    }
+
+    /// Dawns and dusks since the last call, oldest first: 0 = dawn, 1 = dusk
+    pub fn take_day_events(&mut self) -> Vec<u8> {
+        self.world.take_day_events().into_iter().map(|e| e as u8).collect()
+    }
+
+    /// Current season: spring, summer, autumn or winter
+    pub fn get_season(&self) -> String {
+        self.world.season().name().to_string()
+    }
+
+    /// How far through the current season, 0..1
+    pub fn get_season_progress(&self) -> f32 {
+        self.world.season_progress()
+    }
+
+    /// Multiplier for the host's birth chance in the current season
+    pub fn get_breeding_mult(&self) -> f32 {
+        self.world.breeding_mult()
+    }
+
+    /// Parameters of a season, in the order of `SeasonParams::NAMES`:
+    /// [bug_rate, max_bugs, food_chance, day_length, breeding_mult, predator_aggression].
+    /// Empty for an unknown season.
+    pub fn get_season_params(&self, season: &str) -> Vec<f32> {
+        let Some(season) = Season::from_name(season) else { return Vec::new() };
+        let params = self.world.seasons.get(season);
+        SeasonParams::NAMES.iter().filter_map(|name| params.get(name)).collect()
+    }
+
+    /// Set one parameter of a season by name, e.g. ("winter", "bug_rate", 0.01).
+    /// Returns false if either name is unknown.
+    pub fn set_season_param(&mut self, season: &str, param: &str, value: f32) -> bool {
+        let Some(season) = Season::from_name(season) else { return false };
+        self.world.seasons.get_mut(season).set(param, value)
+    }
+
+    /// Name of the current weather: clear, rain, storm or snow
+    pub fn get_weather(&self) -> String {
+        self.world.weather.current.name().to_string()
+    }
+
+    /// Force the weather by name. `auto` lets it keep changing with the
+    /// seasons afterwards. Returns false for an unknown name.
+    pub fn set_weather(&mut self, name: &str, auto: bool) -> bool {
+        let Some(weather) = Weather::from_name(name) else { return false };
+        self.world.weather.set(weather);
+        self.world.weather.auto = auto;
+        true
     }
 
     /// Get boid render data as flat Float32Array
@@ -58,12 +202,43 @@ impl WasmWorld {
     }
 
     /// Get bug render data as flat Float32Array
-    /// Format: [x, y, hue, size, ...]
-    /// 4 floats per bug
+    /// Format: [x, y, hue, size, brightness, ...]
+    /// 5 floats per bug; brightness is a firefly's glow (0..1), 0 for other bugs.
+    /// Fireflies are listed with the other bugs from dusk until dawn.
     pub fn get_bug_data(&self) -> Vec<f32> {
         self.world.get_bug_render_data()
     }
 
+    /// Kind of every bug, same order as get_bug_data: 0 = common, 1 = firefly
+    pub fn get_bug_kinds(&self) -> Vec<u8> {
+        self.world.bugs.iter().map(|b| b.kind as u8).collect()
+    }
+
+    /// Get previous-step boid positions as flat Float32Array
+    /// Format: [prev_x, prev_y, ...], 2 floats per boid, same order as get_boid_data.
+    /// Positions are unwrapped across edges, so lerp(prev, current, alpha) is always safe.
+    pub fn get_boid_prev_data(&self) -> Vec<f32> {
+        self.world.get_boid_prev_data()
+    }
+
+    /// Get previous-step predator positions as flat Float32Array
+    /// Format: [prev_x, prev_y, ...], 2 floats per predator, same order as get_predator_data
+    pub fn get_predator_prev_data(&self) -> Vec<f32> {
+        self.world.get_predator_prev_data()
+    }
+
+    /// Get previous-step bug positions as flat Float32Array
+    /// Format: [prev_x, prev_y, ...], 2 floats per bug, same order as get_bug_data
+    pub fn get_bug_prev_data(&self) -> Vec<f32> {
+        self.world.get_bug_prev_data()
+    }
+
+    /// Export the current state as a standalone SVG document.
+    /// Optionally includes velocity vectors and links between flock-mates.
+    pub fn export_svg(&self, velocity_vectors: bool, neighbour_links: bool) -> String {
+        self.world.export_svg(svg::SvgOptions { velocity_vectors, neighbour_links })
+    }
+
     /// Add a boid at position
     pub fn add_boid(&mut self, x: f32, y: f32, species: u8) {
         self.world.add_boid(x, y, species);
@@ -71,12 +246,7 @@ impl WasmWorld {
 
     /// Add multiple boids at position
     pub fn add_boids(&mut self, x: f32, y: f32, count: u32) {
-        for i in 0..count {
-            let ox = (i as f32 - count as f32 / 2.0) * 5.0 + (rand_f32() - 0.5) * 20.0;
-            let oy = (rand_f32() - 0.5) * 20.0;
-            let species = if rand_f32() > 0.5 { 0 } else { 1 };
-            self.world.add_boid(x + ox, y + oy, species);
-        }
+        self.world.add_boids(x, y, count);
     }
 
     /// Add a predator at position
@@ -84,18 +254,114 @@ impl WasmWorld {
         self.world.add_predator(x, y);
     }
 
-    /// Add an obstacle at position
+    /// Add an obstacle at position with the default rock radius
     pub fn add_obstacle(&mut self, x: f32, y: f32) {
-        self.world.add_obstacle(x, y);
+        self.world.add_obstacle(x, y, obstacle::DEFAULT_RADIUS);
     }
 
-    /// Remove obstacle near position (within radius 20)
+    /// Add an obstacle at position with an explicit radius
+    pub fn add_obstacle_with_radius(&mut self, x: f32, y: f32, radius: f32) {
+        self.world.add_obstacle(x, y, radius);
+    }
+
+    /// Remove obstacles, walls and polygons near position (within radius 20)
     pub fn remove_obstacle(&mut self, x: f32, y: f32) {
-        self.world.obstacles.retain(|&(ox, oy)| {
-            let dx = ox - x;
-            let dy = oy - y;
-            dx * dx + dy * dy > 400.0 // 20^2
-        });
+        self.world.remove_obstacle(x, y, 20.0);
+    }
+
+    /// Add a solid line-segment wall
+    pub fn add_wall(&mut self, x1: f32, y1: f32, x2: f32, y2: f32) {
+        self.world.add_wall(x1, y1, x2, y2);
+    }
+
+    /// Add a solid convex polygon from flat [x1, y1, x2, y2, ...]. Points are
+    /// wrapped in their convex hull. Returns false if they enclose no area.
+    pub fn add_polygon(&mut self, points: Vec<f32>) -> bool {
+        let points: Vec<Vec2> = points.chunks_exact(2).map(|p| Vec2::new(p[0], p[1])).collect();
+        self.world.add_polygon(&points)
+    }
+
+    /// Get every obstacle shape as one flat array of tagged records:
+    /// circle [0, x, y, radius], wall [1, x1, y1, x2, y2],
+    /// polygon [2, n, x1, y1, ..., xn, yn]
+    pub fn get_geometry_data(&self) -> Vec<f32> {
+        self.world.get_geometry_render_data()
+    }
+
+    /// Set the steady wind, as an acceleration per tick
+    pub fn set_wind(&mut self, x: f32, y: f32) {
+        self.world.flow.wind = Vec2::new(x, y);
+    }
+
+    /// Set gust strength (0 for none) and size in pixels
+    pub fn set_gusts(&mut self, strength: f32, scale: f32) {
+        self.world.flow.gust_strength = strength.max(0.0);
+        self.world.flow.gust_scale = scale.max(1.0);
+    }
+
+    /// Add a vortex; positive strength turns clockwise
+    pub fn add_vortex(&mut self, x: f32, y: f32, radius: f32, strength: f32) {
+        self.world.flow.add_vortex(x, y, radius, strength);
+    }
+
+    /// Remove vortices centred near position (within radius 20)
+    pub fn remove_vortex(&mut self, x: f32, y: f32) {
+        self.world.flow.remove_vortex_near(x, y, 20.0);
+    }
+
+    /// Sample the wind on a grid for drawing arrows
+    /// Format: [x, y, fx, fy, ...], 4 floats per grid cell
+    pub fn get_flow_data(&self, spacing: f32) -> Vec<f32> {
+        self.world.get_flow_render_data(spacing)
+    }
+
+    /// Replace the terrain with a `cols` by `rows` map of zone indices, row by
+    /// row: 0 = meadow, 1 = forest, 2 = water, 3 = rock, 4 = safe. It is
+    /// stretched over the world. Returns false if the sizes do not match.
+    pub fn set_terrain(&mut self, cols: usize, rows: usize, zones: Vec<u8>) -> bool {
+        let Some(map) = TerrainMap::from_indices(cols, rows, &zones, self.world.width, self.world.height) else { return false };
+        self.world.set_terrain(map);
+        true
+    }
+
+    /// Replace the terrain from an image's RGBA pixels, one pixel per cell,
+    /// each taking the zone of the nearest colour in the palette the zones
+    /// are drawn with. Returns false if the sizes do not match.
+    pub fn set_terrain_rgba(&mut self, cols: usize, rows: usize, pixels: Vec<u8>) -> bool {
+        let Some(map) = TerrainMap::from_rgba(cols, rows, &pixels, self.world.width, self.world.height) else { return false };
+        self.world.set_terrain(map);
+        true
+    }
+
+    /// Get the terrain map: [cols, rows, zone, zone, ...] with zones row by row
+    pub fn get_terrain_data(&self) -> Vec<f32> {
+        self.world.get_terrain_render_data()
+    }
+
+    /// Get one scent channel as a heatmap: [cols, rows, value, ...] row by
+    /// row. channel: 0 = food (species 0), 1 = danger (species 0),
+    /// 2 = food (species 1), 3 = danger (species 1), 4 = boid trail.
+    /// Empty for an unknown channel.
+    pub fn get_scent_data(&self, channel: usize) -> Vec<f32> {
+        Channel::from_index(channel).map_or_else(Vec::new, |c| self.world.get_scent_render_data(c))
+    }
+
+    /// Set how fast scent spreads and fades, each a share per tick (0..1)
+    pub fn set_scent_rates(&mut self, diffusion: f32, evaporation: f32) {
+        self.world.scent.diffusion = diffusion.clamp(0.0, 1.0);
+        self.world.scent.evaporation = evaporation.clamp(0.0, 1.0);
+    }
+
+    /// Get territory ownership: [cols, rows, owner, strength, ...] row by row.
+    /// owner: -1 = unclaimed, 0/1 = boid species, 2 + n = predator pack n.
+    /// strength: 0..1
+    pub fn get_territory_data(&self) -> Vec<f32> {
+        self.world.get_territory_render_data()
+    }
+
+    /// Wipe every scent trail
+    pub fn clear_scent(&mut self) {
+        self.world.scent.clear();
     }
 
     /// Add a food source at position
@@ -103,6 +369,53 @@ impl WasmWorld {
         self.world.add_food(x, y);
     }
 
+    /// Names of the steering behaviours in the order they run
+    pub fn get_behaviour_names(&self) -> Vec<String> {
+        self.world.behaviours.entries().iter().map(|e| e.behaviour.name().to_string()).collect()
+    }
+
+    /// Set a behaviour's weight. Returns false if there is no behaviour by that name.
+    pub fn set_behaviour_weight(&mut self, name: &str, weight: f32) -> bool {
+        self.world.behaviours.set_weight(name, weight)
+    }
+
+    /// Set a behaviour's radius. Returns false if there is no behaviour by that name.
+    pub fn set_behaviour_radius(&mut self, name: &str, radius: f32) -> bool {
+        self.world.behaviours.set_radius(name, radius)
+    }
+
+    /// Turn a behaviour on or off. Returns false if there is no behaviour by that name.
+    pub fn set_behaviour_enabled(&mut self, name: &str, enabled: bool) -> bool {
+        self.world.behaviours.set_enabled(name, enabled)
+    }
+
+    /// Move a behaviour to position `index` in the pipeline
+    pub fn move_behaviour(&mut self, name: &str, index: usize) -> bool {
+        self.world.behaviours.move_to(name, index)
+    }
+
+    /// Align and cohere with the `k` nearest flock-mates instead of everyone
+    /// within a radius; 0 switches back to radii
+    pub fn set_topological_neighbours(&mut self, k: u32) {
+        self.world.neighbour_mode = match k {
+            0 => NeighbourMode::Metric,
+            k => NeighbourMode::Topological(k as usize),
+        };
+    }
+
+    /// Set the view cone of boid species 0 or 1: full width in degrees (360
+    /// for all-round vision) and range
+    pub fn set_vision(&mut self, species: u8, fov_degrees: f32, range: f32) {
+        if let Some(v) = self.world.vision.get_mut(species as usize) {
+            *v = Vision::new(fov_degrees, range);
+        }
+    }
+
+    /// Set the view cone predators spot prey with
+    pub fn set_predator_vision(&mut self, fov_degrees: f32, range: f32) {
+        self.world.predator_vision = Vision::new(fov_degrees, range);
+    }
+
     /// Get current width
     pub fn width(&self) -> f32 {
         self.world.width
@@ -115,15 +428,32 @@ impl WasmWorld {
 
     /// Reset the world
     pub fn reset(&mut self, width: f32, height: f32, start_boids: u32) {
-        self.world = World::new(width, height, start_boids);
+        let mut clock = self.world.clock;
+        clock.reset();
+        let behaviours = std::mem::take(&mut self.world.behaviours);
+        let (vision, predator_vision) = (self.world.vision, self.world.predator_vision);
+        let neighbour_mode = self.world.neighbour_mode;
+        let (seasons, day_step) = (self.world.seasons, self.world.day_step);
+        let terrain = self.world.terrain.stretched(width, height);
+        self.world = World::with_terrain(width, height, start_boids, terrain);
+        self.world.clock = clock;
+        self.world.behaviours = behaviours;
+        self.world.vision = vision;
+        self.world.predator_vision = predator_vision;
+        self.world.neighbour_mode = neighbour_mode;
+        self.world.seasons = seasons;
+        self.world.day_step = day_step;
     }
 
-    /// Get obstacle positions as flat array [x, y, x, y, ...]
+    /// Get circular obstacle data as flat Float32Array
+    /// Format: [x, y, radius, ...]
+    /// 3 floats per obstacle; walls and polygons are in get_geometry_data
     pub fn get_obstacle_data(&self) -> Vec<f32> {
-        let mut data = Vec::with_capacity(self.world.obstacles.len() * 2);
-        for &(x, y) in &self.world.obstacles {
-            data.push(x);
-            data.push(y);
+        let mut data = Vec::with_capacity(self.world.obstacles.len() * 3);
+        for o in self.world.obstacles.as_slice() {
+            data.push(o.position.x);
+            data.push(o.position.y);
+            data.push(o.radius);
         }
         data
     }
@@ -140,12 +470,22 @@ impl WasmWorld {
     }
 }
 
-fn rand_f32() -> f32 {
-    static mut SEED: u32 = 77777;
-    unsafe {
-        SEED ^= SEED << 13;
-        SEED ^= SEED >> 17;
-        SEED ^= SEED << 5;
-        (SEED as f32) / (u32::MAX as f32)
-    }
+/// Reseed every random number generator in the simulation. Two runs with
+/// the same seed and the same inputs produce identical worlds.
+#[wasm_bindgen]
+pub fn set_seed(seed: u32) {
+    let stream = |salt: u32| seed.wrapping_mul(0x9E37_79B9) ^ salt;
+    boid::set_seed(stream(12345));
+    predator::set_seed(stream(67890));
+    bug::set_seed(stream(11111));
+    world::set_seed(stream(99999));
+    weather::set_seed(stream(24680));
+}
+
+/// Held by tests that seed or draw from the generators, so a seeded run
+/// is not disturbed by another test on a different thread
+#[cfg(test)]
+pub(crate) fn rng_lock() -> std::sync::MutexGuard<'static, ()> {
+    static LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+    LOCK.lock().unwrap_or_else(|e| e.into_inner())
 }
@@ -0,0 +1,100 @@
+use crate::boid::BoidState;
+use crate::clock::STEP_MS;
+use crate::weather::Weather;
+use crate::world::World;
+
+/// Population-level summary of a world, for headless runs and experiments
+#[derive(Clone, Copy, Default)]
+pub struct Metrics {
+    pub tick: u32,
+    pub boids: u32,
+    pub predators: u32,
+    pub bugs: u32,
+    pub day_phase: f32,
+    pub species0: u32,
+    pub species1: u32,
+    pub hybrids: u32,
+    pub mean_energy: f32,
+    pub mean_fatigue: f32,
+    pub mean_fear: f32,
+    pub collapsed: u32,
+    pub mean_mutations: f32,
+    pub max_generation: u32,
+    pub predator_kills: u32,
+    pub food_sources: u32,
+    pub weather: Weather,
+    /// Share of the world each species holds as territory, 0..1
+    pub territory0: f32,
+    pub territory1: f32,
+}
+
+impl Metrics {
+    pub const CSV_HEADER: &'static str = "tick,boids,predators,bugs,day_phase,species0,species1,hybrids,\
+mean_energy,mean_fatigue,mean_fear,collapsed,mean_mutations,max_generation,predator_kills,food_sources,weather,territory0,territory1";
+
+    pub fn from_world(world: &World) -> Self {
+        let (boids, predators, bugs, day_phase, weather) = world.get_stats();
+        let mut m = Metrics {
+            tick: world.time / STEP_MS as u32,
+            boids,
+            predators,
+            bugs,
+            day_phase,
+            food_sources: world.food_sources.len() as u32,
+            weather,
+            territory0: world.territory_share(0),
+            territory1: world.territory_share(1),
+            ..Default::default()
+        };
+
+        for b in &world.boids {
+            if b.is_hybrid { m.hybrids += 1; }
+            else if b.species == 0 { m.species0 += 1; }
+            else { m.species1 += 1; }
+            if b.state == BoidState::Collapsed { m.collapsed += 1; }
+            m.mean_energy += b.energy;
+            m.mean_fatigue += b.fatigue;
+            m.mean_fear += b.fear;
+            m.mean_mutations += b.mutations.count() as f32;
+        }
+        if boids > 0 {
+            let n = boids as f32;
+            m.mean_energy /= n;
+            m.mean_fatigue /= n;
+            m.mean_fear /= n;
+            m.mean_mutations /= n;
+        }
+
+        for p in &world.predators {
+            m.max_generation = m.max_generation.max(p.generation);
+            m.predator_kills += p.kills;
+        }
+        m
+    }
+
+    pub fn to_csv(&self) -> String {
+        format!(
+            "{},{},{},{},{:.4},{},{},{},{:.3},{:.3},{:.4},{},{:.4},{},{},{},{},{:.4},{:.4}",
+            self.tick, self.boids, self.predators, self.bugs, self.day_phase,
+            self.species0, self.species1, self.hybrids,
+            self.mean_energy, self.mean_fatigue, self.mean_fear, self.collapsed,
+            self.mean_mutations, self.max_generation, self.predator_kills, self.food_sources,
+            self.weather.name(), self.territory0, self.territory1,
+        )
+    }
+
+    /// One JSON object on a single line (newline-delimited JSON)
+    pub fn to_ndjson(&self) -> String {
+        format!(
+            "{{\"tick\":{},\"boids\":{},\"predators\":{},\"bugs\":{},\"day_phase\":{:.4},\
+\"species0\":{},\"species1\":{},\"hybrids\":{},\"mean_energy\":{:.3},\"mean_fatigue\":{:.3},\
+\"mean_fear\":{:.4},\"collapsed\":{},\"mean_mutations\":{:.4},\"max_generation\":{},\
+\"predator_kills\":{},\"food_sources\":{},\"weather\":\"{}\",\"territory0\":{:.4},\"territory1\":{:.4}}}",
+            self.tick, self.boids, self.predators, self.bugs, self.day_phase,
+            self.species0, self.species1, self.hybrids,
+            self.mean_energy, self.mean_fatigue, self.mean_fear, self.collapsed,
+            self.mean_mutations, self.max_generation, self.predator_kills, self.food_sources,
+            self.weather.name(), self.territory0, self.territory1,
+        )
+    }
+}
@@ -0,0 +1,514 @@
+use crate::spatial::SpatialHash;
+use crate::vector::Vec2;
+
+/// Radius of a rock placed without an explicit size (matches the drawn rock)
+pub const DEFAULT_RADIUS: f32 = 14.0;
+/// Distance beyond an obstacle's surface at which boids and predators start steering away
+pub const AVOID_MARGIN: f32 = 36.0;
+/// Wider margin used by frightened boids, which give rocks a larger berth
+pub const PANIC_AVOID_MARGIN: f32 = 66.0;
+
+const CELL_SIZE: f32 = 50.0;
+/// Walls whose bounding box covers more cells than this are checked
+/// everywhere instead of being indexed
+const MAX_SEGMENT_CELLS: f32 = 4096.0;
+
+/// A solid circular obstacle. Nothing may overlap its `radius`.
+#[derive(Clone, Copy)]
+pub struct Obstacle {
+    pub position: Vec2,
+    pub radius: f32,
+}
+
+/// A solid line segment. Nothing may come closer than its own radius.
+#[derive(Clone, Copy)]
+pub struct Wall {
+    pub a: Vec2,
+    pub b: Vec2,
+}
+
+impl Wall {
+    pub fn new(x1: f32, y1: f32, x2: f32, y2: f32) -> Self {
+        Self { a: Vec2::new(x1, y1), b: Vec2::new(x2, y2) }
+    }
+
+    pub fn closest_point(&self, p: Vec2) -> Vec2 {
+        let ab = self.b.sub(self.a);
+        let len_sq = ab.mag_sq();
+        let t = if len_sq > 0.0 { (p.sub(self.a).dot(ab) / len_sq).clamp(0.0, 1.0) } else { 0.0 };
+        self.a.add(ab.mult(t))
+    }
+
+    /// Where the path from `from` to `to` crosses the wall, as a fraction of the path
+    fn crossing(&self, from: Vec2, to: Vec2) -> Option<f32> {
+        let r = to.sub(from);
+        let s = self.b.sub(self.a);
+        let denom = r.cross(s);
+        if denom == 0.0 { return None; }
+        let af = self.a.sub(from);
+        let t = af.cross(s) / denom;
+        let u = af.cross(r) / denom;
+        ((0.0..=1.0).contains(&t) && (0.0..=1.0).contains(&u)).then_some(t)
+    }
+
+    /// Unit normal pointing to the side `p` is on
+    fn normal_towards(&self, p: Vec2) -> Vec2 {
+        let s = self.b.sub(self.a);
+        let n = Vec2::new(-s.y, s.x).normalize();
+        if p.sub(self.a).dot(n) < 0.0 { n.mult(-1.0) } else { n }
+    }
+}
+
+/// A solid convex polygon
+#[derive(Clone)]
+pub struct Polygon {
+    /// Corners in order around the hull
+    points: Vec<Vec2>,
+}
+
+impl Polygon {
+    /// The convex hull of `points`, or `None` if they do not enclose an area
+    pub fn new(points: &[Vec2]) -> Option<Self> {
+        let mut pts: Vec<Vec2> = points.iter().copied().filter(|p| p.x.is_finite() && p.y.is_finite()).collect();
+        pts.sort_by(|a, b| a.x.total_cmp(&b.x).then(a.y.total_cmp(&b.y)));
+        pts.dedup_by(|a, b| a.x == b.x && a.y == b.y);
+        if pts.len() < 3 { return None; }
+
+        // Monotone chain: lower hull, then upper hull
+        let mut hull = half_hull(pts.iter().copied());
+        hull.extend(half_hull(pts.iter().rev().copied()));
+        (hull.len() >= 3).then_some(Self { points: hull })
+    }
+
+    pub fn points(&self) -> &[Vec2] {
+        &self.points
+    }
+
+    pub fn edges(&self) -> impl Iterator<Item = Wall> + '_ {
+        let n = self.points.len();
+        (0..n).map(move |i| Wall { a: self.points[i], b: self.points[(i + 1) % n] })
+    }
+
+    pub fn contains(&self, p: Vec2) -> bool {
+        self.edges().all(|e| e.b.sub(e.a).cross(p.sub(e.a)) > 0.0)
+    }
+}
+
+/// One side of a convex hull over points sorted by x, turning left throughout.
+/// The last point is dropped, as it starts the other side.
+fn half_hull(points: impl Iterator<Item = Vec2>) -> Vec<Vec2> {
+    let mut hull: Vec<Vec2> = Vec::new();
+    for p in points {
+        while let [.., a, b] = hull[..] {
+            if b.sub(a).cross(p.sub(a)) > 0.0 { break; }
+            hull.pop();
+        }
+        hull.push(p);
+    }
+    hull.pop();
+    hull
+}
+
+/// All obstacles in the world: circles, walls and convex polygons, with a
+/// spatial index over the circles and another over the walls and edges.
+///
+/// Obstacles rarely change, so the index is only rebuilt on add/remove
+/// rather than every tick.
+pub struct Obstacles {
+    list: Vec<Obstacle>,
+    hash: SpatialHash,
+    max_radius: f32,
+    walls: Vec<Wall>,
+    polygons: Vec<Polygon>,
+    /// Walls plus every polygon edge, for collision and avoidance
+    segments: Vec<Wall>,
+    /// Each segment under every cell its bounding box covers
+    segment_hash: SpatialHash,
+    /// Segments too long to index
+    long_segments: Vec<usize>,
+}
+
+impl Obstacles {
+    pub fn new() -> Self {
+        Self {
+            list: Vec::new(),
+            hash: SpatialHash::new(CELL_SIZE),
+            max_radius: 0.0,
+            walls: Vec::new(),
+            polygons: Vec::new(),
+            segments: Vec::new(),
+            segment_hash: SpatialHash::new(CELL_SIZE),
+            long_segments: Vec::new(),
+        }
+    }
+
+    pub fn add(&mut self, x: f32, y: f32, radius: f32) {
+        self.list.push(Obstacle { position: Vec2::new(x, y), radius });
+        self.rebuild();
+    }
+
+    pub fn add_wall(&mut self, x1: f32, y1: f32, x2: f32, y2: f32) {
+        self.walls.push(Wall::new(x1, y1, x2, y2));
+        self.rebuild();
+    }
+
+    /// Add the convex hull of `points` as a solid polygon. Returns false if
+    /// the points do not enclose an area.
+    pub fn add_polygon(&mut self, points: &[Vec2]) -> bool {
+        let Some(polygon) = Polygon::new(points) else { return false };
+        self.polygons.push(polygon);
+        self.rebuild();
+        true
+    }
+
+    /// Remove every circle whose centre is within `dist` of the point, every
+    /// wall passing within `dist`, and every polygon within `dist`
+    pub fn remove_near(&mut self, x: f32, y: f32, dist: f32) {
+        let before = (self.list.len(), self.walls.len(), self.polygons.len());
+        let p = Vec2::new(x, y);
+        let d_sq = dist * dist;
+        self.list.retain(|o| o.position.dist_sq(p) > d_sq);
+        self.walls.retain(|w| w.closest_point(p).dist_sq(p) > d_sq);
+        self.polygons.retain(|poly| !poly.contains(p) && poly.edges().all(|e| e.closest_point(p).dist_sq(p) > d_sq));
+        if (self.list.len(), self.walls.len(), self.polygons.len()) != before {
+            self.rebuild();
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.list.clear();
+        self.walls.clear();
+        self.polygons.clear();
+        self.rebuild();
+    }
+
+    /// Circular obstacles
+    #[inline]
+    pub fn as_slice(&self) -> &[Obstacle] {
+        &self.list
+    }
+
+    /// Number of circular obstacles
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.list.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.list.is_empty()
+    }
+
+    pub fn walls(&self) -> &[Wall] {
+        &self.walls
+    }
+
+    pub fn polygons(&self) -> &[Polygon] {
+        &self.polygons
+    }
+
+    fn rebuild(&mut self) {
+        self.hash.clear();
+        self.max_radius = 0.0;
+        for (i, o) in self.list.iter().enumerate() {
+            self.hash.insert(i, o.position.x, o.position.y);
+            self.max_radius = self.max_radius.max(o.radius);
+        }
+        self.segments.clear();
+        self.segments.extend_from_slice(&self.walls);
+        for poly in &self.polygons {
+            self.segments.extend(poly.edges());
+        }
+        self.segment_hash.clear();
+        self.long_segments.clear();
+        for (i, w) in self.segments.iter().enumerate() {
+            let (min, max) = bounds(w);
+            let cells = ((max.x / CELL_SIZE).floor() - (min.x / CELL_SIZE).floor() + 1.0)
+                * ((max.y / CELL_SIZE).floor() - (min.y / CELL_SIZE).floor() + 1.0);
+            if cells <= MAX_SEGMENT_CELLS {
+                self.segment_hash.insert_box(i, min.x, min.y, max.x, max.y);
+            } else {
+                self.long_segments.push(i);
+            }
+        }
+    }
+
+    /// Calls `f` once for every wall or polygon edge whose bounding box may
+    /// be within `reach` of the point
+    #[inline]
+    fn for_each_segment_near(&self, p: Vec2, reach: f32, mut f: impl FnMut(&Wall)) {
+        for &i in &self.long_segments {
+            f(&self.segments[i]);
+        }
+        let cells = (reach / CELL_SIZE).ceil() as i32;
+        let (cx, cy) = self.segment_hash.get_key(p.x, p.y);
+        self.segment_hash.for_each_nearby_cell(p.x, p.y, cells, |key, i| {
+            // A segment is in every cell its box covers; only take it in the
+            // first of those the query also covers
+            let w = &self.segments[i];
+            let (min, _) = bounds(w);
+            let (sx, sy) = self.segment_hash.get_key(min.x, min.y);
+            if key == (sx.max(cx - cells), sy.max(cy - cells)) {
+                f(w);
+            }
+        });
+    }
+
+    /// Calls `f` for every obstacle whose surface may be within `reach` of the point
+    #[inline]
+    pub fn for_each_near(&self, x: f32, y: f32, reach: f32, mut f: impl FnMut(&Obstacle)) {
+        if self.list.is_empty() { return; }
+        let cells = ((reach + self.max_radius) / CELL_SIZE).ceil() as i32;
+        self.hash.for_each_nearby(x, y, cells, |i| f(&self.list[i]));
+    }
+
+    /// Sum of inverse-square push directions away from every obstacle whose
+    /// surface is within `margin` of the point. Walls and polygon edges are
+    /// also ray-cast along `velocity`, so one ahead is turned from before it
+    /// is close. Zero when nothing is in range.
+    pub fn avoidance(&self, x: f32, y: f32, velocity: Vec2, margin: f32) -> (f32, f32) {
+        let mut ax = 0.0f32;
+        let mut ay = 0.0f32;
+        self.for_each_near(x, y, margin, |o| {
+            let dx = x - o.position.x;
+            let dy = y - o.position.y;
+            let dsq = dx * dx + dy * dy;
+            let reach = o.radius + margin;
+            if dsq < reach * reach {
+                let inv = 1.0 / (dsq + 0.001);
+                ax += dx * inv;
+                ay += dy * inv;
+            }
+        });
+        if !self.segments.is_empty() {
+            let (sx, sy) = self.segment_avoidance(Vec2::new(x, y), velocity, margin);
+            ax += sx;
+            ay += sy;
+        }
+        (ax, ay)
+    }
+
+    fn segment_avoidance(&self, p: Vec2, velocity: Vec2, margin: f32) -> (f32, f32) {
+        let (mut ax, mut ay) = (0.0f32, 0.0f32);
+        // Look twice the margin ahead and turn from the first wall in the way
+        let speed = velocity.mag();
+        let lookahead = margin * 2.0;
+        let ahead = (speed > 0.0).then(|| p.add(velocity.mult(lookahead / speed)));
+        let mut first: Option<(f32, Wall)> = None;
+        self.for_each_segment_near(p, lookahead, |w| {
+            let d = p.sub(w.closest_point(p));
+            let dsq = d.mag_sq();
+            if dsq < margin * margin {
+                let inv = 1.0 / (dsq + 0.001);
+                ax += d.x * inv;
+                ay += d.y * inv;
+            }
+            if let Some(t) = ahead.and_then(|ahead| w.crossing(p, ahead)) {
+                if first.is_none_or(|(best, _)| t < best) {
+                    first = Some((t, *w));
+                }
+            }
+        });
+        if let Some((t, w)) = first {
+            let n = w.normal_towards(p);
+            let inv = 1.0 / (t * lookahead + 1.0);
+            ax += n.x * inv;
+            ay += n.y * inv;
+        }
+        (ax, ay)
+    }
+
+    /// Push a body of `body_radius` out of any obstacle it overlaps and remove
+    /// the part of its velocity heading into the obstacle. `velocity` must be
+    /// the step just taken, so bodies that crossed a wall during the step are
+    /// stopped at it. Returns true on contact.
+    pub fn collide(&self, position: &mut Vec2, velocity: &mut Vec2, body_radius: f32) -> bool {
+        let mut hit = self.collide_circles(position, velocity, body_radius);
+        if !self.segments.is_empty() {
+            hit |= self.collide_segments(position, velocity, body_radius);
+        }
+        hit
+    }
+
+    fn collide_circles(&self, position: &mut Vec2, velocity: &mut Vec2, body_radius: f32) -> bool {
+        let mut hit = false;
+        let (x, y) = (position.x, position.y);
+        let mut push_x = 0.0f32;
+        let mut push_y = 0.0f32;
+        let mut normal_x = 0.0f32;
+        let mut normal_y = 0.0f32;
+        self.for_each_near(x, y, body_radius, |o| {
+            let dx = x - o.position.x;
+            let dy = y - o.position.y;
+            let min_d = o.radius + body_radius;
+            let dsq = dx * dx + dy * dy;
+            if dsq < min_d * min_d {
+                let d = dsq.sqrt();
+                let (nx, ny) = if d > 0.0 { (dx / d, dy / d) } else { (1.0, 0.0) };
+                push_x += nx * (min_d - d);
+                push_y += ny * (min_d - d);
+                normal_x += nx;
+                normal_y += ny;
+                hit = true;
+            }
+        });
+        if hit {
+            position.x += push_x;
+            position.y += push_y;
+            slide(velocity, Vec2::new(normal_x, normal_y).normalize());
+        }
+        hit
+    }
+
+    fn collide_segments(&self, position: &mut Vec2, velocity: &mut Vec2, body_radius: f32) -> bool {
+        let mut hit = false;
+
+        // Stop at the first wall crossed this step, so fast movers cannot tunnel
+        let from = position.sub(*velocity);
+        let mut first: Option<(f32, Wall)> = None;
+        self.for_each_segment_near(*position, velocity.mag(), |w| {
+            if let Some(t) = w.crossing(from, *position) {
+                if first.is_none_or(|(best, _)| t < best) {
+                    first = Some((t, *w));
+                }
+            }
+        });
+        if let Some((t, w)) = first {
+            let n = w.normal_towards(from);
+            *position = from.add(velocity.mult(t)).add(n.mult(body_radius));
+            slide(velocity, n);
+            hit = true;
+        }
+
+        // Anything inside a polygon (placed there, or squeezed past an edge) leaves by the nearest edge
+        for poly in &self.polygons {
+            if !poly.contains(*position) { continue; }
+            let p = *position;
+            let nearest = poly.edges()
+                .map(|e| e.closest_point(p))
+                .min_by(|a, b| a.dist_sq(p).total_cmp(&b.dist_sq(p)));
+            if let Some(q) = nearest {
+                let n = q.sub(p).normalize();
+                *position = q.add(n.mult(body_radius));
+                slide(velocity, n);
+                hit = true;
+            }
+        }
+
+        // Keep the body's radius clear of every wall
+        let p = *position;
+        let (mut push, mut normal) = (Vec2::zero(), Vec2::zero());
+        self.for_each_segment_near(p, body_radius, |w| {
+            let q = w.closest_point(p);
+            let d_sq = p.dist_sq(q);
+            if d_sq < body_radius * body_radius {
+                let d = d_sq.sqrt();
+                let n = if d > 0.0 { p.sub(q).div(d) } else { w.normal_towards(from) };
+                push.add_mut(n.mult(body_radius - d));
+                normal.add_mut(n);
+                hit = true;
+            }
+        });
+        if normal.x != 0.0 || normal.y != 0.0 {
+            position.add_mut(push);
+            slide(velocity, normal.normalize());
+        }
+        hit
+    }
+}
+
+/// Corners of the box around a segment
+#[inline]
+fn bounds(w: &Wall) -> (Vec2, Vec2) {
+    (
+        Vec2::new(w.a.x.min(w.b.x), w.a.y.min(w.b.y)),
+        Vec2::new(w.a.x.max(w.b.x), w.a.y.max(w.b.y)),
+    )
+}
+
+/// Remove the part of `velocity` heading against the surface normal `n`
+#[inline]
+fn slide(velocity: &mut Vec2, n: Vec2) {
+    let into = velocity.x * n.x + velocity.y * n.y;
+    if into < 0.0 {
+        velocity.x -= n.x * into;
+        velocity.y -= n.y * into;
+    }
+}
+
+impl Default for Obstacles {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn square(x: f32, y: f32, size: f32) -> Vec<Vec2> {
+        vec![Vec2::new(x, y), Vec2::new(x + size, y), Vec2::new(x + size, y + size), Vec2::new(x, y + size)]
+    }
+
+    #[test]
+    fn hull_of_unordered_points_drops_collinear_duplicate_and_inner_points() {
+        let points = [
+            Vec2::new(10.0, 10.0), Vec2::new(0.0, 0.0), Vec2::new(5.0, 0.0),
+            Vec2::new(10.0, 0.0), Vec2::new(4.0, 6.0), Vec2::new(0.0, 10.0),
+            Vec2::new(0.0, 0.0), Vec2::new(10.0, 5.0), Vec2::new(10.0, 10.0),
+        ];
+        let poly = Polygon::new(&points).unwrap();
+        let mut corners: Vec<_> = poly.points().iter().map(|p| (p.x, p.y)).collect();
+        corners.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        assert_eq!(corners, [(0.0, 0.0), (0.0, 10.0), (10.0, 0.0), (10.0, 10.0)]);
+        assert!(poly.contains(Vec2::new(5.0, 5.0)));
+        assert!(!poly.contains(Vec2::new(15.0, 5.0)));
+
+        let line = [Vec2::new(0.0, 0.0), Vec2::new(5.0, 5.0), Vec2::new(10.0, 10.0), Vec2::new(5.0, 5.0)];
+        assert!(Polygon::new(&line).is_none());
+    }
+
+    #[test]
+    fn fast_mover_does_not_tunnel_through_thin_wall() {
+        let mut obstacles = Obstacles::new();
+        obstacles.add_wall(100.0, 0.0, 100.0, 200.0);
+        // The step from x = 90 lands well past the wall
+        let mut velocity = Vec2::new(50.0, 0.0);
+        let mut position = Vec2::new(140.0, 100.0);
+        assert!(obstacles.collide(&mut position, &mut velocity, 3.0));
+        assert!(position.x <= 97.0, "ended up at {}", position.x);
+        assert_eq!(velocity.x, 0.0);
+    }
+
+    #[test]
+    fn point_inside_polygon_leaves_by_nearest_edge() {
+        let mut obstacles = Obstacles::new();
+        assert!(obstacles.add_polygon(&square(0.0, 0.0, 100.0)));
+        let mut position = Vec2::new(10.0, 50.0);
+        let mut velocity = Vec2::new(1.0, 0.0);
+        assert!(obstacles.collide(&mut position, &mut velocity, 2.0));
+        assert_eq!((position.x, position.y), (-2.0, 50.0));
+        assert_eq!(velocity.x, 0.0);
+    }
+
+    #[test]
+    fn walls_are_found_once_whether_indexed_or_long() {
+        let mut obstacles = Obstacles::new();
+        // Spans five cells, so the query below shares several with it
+        obstacles.add_wall(0.0, 100.0, 200.0, 100.0);
+        assert!(obstacles.long_segments.is_empty());
+        let (_, ay) = obstacles.avoidance(125.0, 90.0, Vec2::zero(), AVOID_MARGIN);
+        assert!((ay + 10.0 / 100.001).abs() < 1e-6, "wall counted more than once: {ay}");
+
+        let mut obstacles = Obstacles::new();
+        obstacles.add_wall(0.0, 0.0, 5000.0, 5000.0);
+        assert_eq!(obstacles.long_segments, [0]);
+        let (ax, ay) = obstacles.avoidance(2500.0, 2510.0, Vec2::zero(), AVOID_MARGIN);
+        assert!(ax < 0.0 && ay > 0.0);
+        let mut position = Vec2::new(2520.0, 2530.0);
+        let mut velocity = Vec2::new(0.0, 40.0);
+        assert!(obstacles.collide(&mut position, &mut velocity, 3.0));
+        assert!(position.x > position.y, "crossed the long wall to {position:?}");
+    }
+}
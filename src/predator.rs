@@ -1,10 +1,33 @@
 use crate::vector::Vec2;
+use crate::behaviour::{steer_towards, SCENT_SATURATION};
+use crate::obstacle::{Obstacles, AVOID_MARGIN};
+use crate::spatial::SpatialHash;
+use crate::vision::Vision;
+use crate::flow::FlowField;
 
 const MAX_FORCE: f32 = 0.15;
+const BODY_RADIUS: f32 = 8.0;
+/// Heavier than boids, so the wind moves them less
+const WIND_MULT: f32 = 0.6;
+
+/// Per-tick snapshot of a boid as seen by predators
+#[derive(Clone, Copy)]
+pub struct Prey {
+    pub x: f32,
+    pub y: f32,
+    pub vx: f32,
+    pub vy: f32,
+    pub energy: f32,
+    pub is_collapsed: bool,
+    pub is_tasty: bool,
+    pub in_shelter: bool,
+}
 
 #[derive(Clone)]
 pub struct Predator {
     pub position: Vec2,
+    /// Position before the last update, for render interpolation
+    pub prev_position: Vec2,
     pub velocity: Vec2,
     pub acceleration: Vec2,
     pub energy: f32,
@@ -23,6 +46,7 @@ impl Predator {
         let angle = rand_f32() * std::f32::consts::TAU;
         Self {
             position: Vec2::new(x, y),
+            prev_position: Vec2::new(x, y),
             velocity: Vec2::from_angle(angle, 2.0),
             acceleration: Vec2::zero(),
             energy: 100.0,
@@ -37,45 +61,52 @@ impl Predator {
         }
     }
 
+    /// Pick the best target among the boids in `vision` and steer toward it.
+    /// Predators hunt harder the darker it is (`light` 0..1), and `aggression`
+    /// is the season's multiplier on hunting range and force.
+    /// `nearby` is scratch space for the spatial hash query.
     pub fn hunt(
         &mut self,
-        boid_positions: &[(f32, f32, f32, f32, f32, bool, bool)], // x, y, vx, vy, energy, is_collapsed, is_tasty
-        shelter_positions: &[(f32, f32, f32)], // x, y, radius
-        day_phase: f32,
+        prey: &[Prey],
+        spatial_hash: &SpatialHash,
+        nearby: &mut Vec<usize>,
+        vision: Vision,
+        light: f32,
+        aggression: f32,
     ) -> Option<usize> {
-        let agg = 1.0 + (1.0 - day_phase) * 0.5;
-        self.max_speed = self.base_speed + (1.0 - day_phase) * 2.0;
+        let agg = (1.0 + (1.0 - light) * 0.5) * aggression;
+        self.max_speed = self.base_speed + (1.0 - light) * 2.0;
 
         let px = self.position.x;
         let py = self.position.y;
 
         let mut target_idx: Option<usize> = None;
         let mut target_dist = f32::INFINITY;
-        let max_dist_sq = 22500.0 * agg * agg;
-
-        for (i, &(bx, by, _vx, _vy, energy, is_collapsed, is_tasty)) in boid_positions.iter().enumerate() {
-            // Check if in shelter
-            let mut in_shelter = false;
-            for &(sx, sy, sr) in shelter_positions {
-                let dx = sx - bx;
-                let dy = sy - by;
-                if dx * dx + dy * dy < sr * sr {
-                    in_shelter = true;
-                    break;
-                }
-            }
-            if in_shelter { continue; }
+        // More aggressive in the dark, so they look further afield
+        let range = vision.range() * agg;
+        let max_dist_sq = range * range;
+
+        spatial_hash.get_within_into(px, py, range, nearby);
 
-            let dx = px - bx;
-            let dy = py - by;
+        for &i in nearby.iter() {
+            let Some(boid) = prey.get(i) else { continue };
+            if boid.in_shelter { continue; }
+
+            let dx = px - boid.x;
+            let dy = py - boid.y;
             let d = dx * dx + dy * dy;
+            // Prey approaching from behind is not seen
+            if !vision.in_cone(self.velocity, Vec2::new(-dx, -dy), d) { continue; }
 
             let mut priority = d;
-            if is_tasty { priority *= 0.25; }
-            if is_collapsed { priority *= 0.09; }
-            priority *= (1.0 - energy / 100.0) * 0.5 + 0.5;
+            if boid.is_tasty { priority *= 0.25; }
+            if boid.is_collapsed { priority *= 0.09; }
+            priority *= (1.0 - boid.energy / 100.0) * 0.5 + 0.5;
 
-            if d < max_dist_sq && priority < target_dist {
+            // Ties go to the lowest index, as with a linear scan
+            let better = priority < target_dist
+                || (priority == target_dist && target_idx.is_some_and(|t| i < t));
+            if d < max_dist_sq && better {
                 target_dist = priority;
                 target_idx = Some(i);
             }
@@ -84,7 +115,7 @@ impl Predator {
         self.target_index = target_idx;
 
         if let Some(idx) = target_idx {
-            let (tx, ty, tvx, tvy, _, _, _) = boid_positions[idx];
+            let Prey { x: tx, y: ty, vx: tvx, vy: tvy, .. } = prey[idx];
             // Predict position
             let pred_x = tx + tvx * 8.0;
             let pred_y = ty + tvy * 8.0;
@@ -113,30 +144,36 @@ impl Predator {
         None
     }
 
-    pub fn update(&mut self, width: f32, height: f32, obstacle_positions: &[(f32, f32)]) -> bool {
-        self.energy -= 0.04;
+    /// With no prey in sight, follow `trail` (the boid scent gradient) at up
+    /// to half force, less on a faint trail. Does nothing while hunting.
+    pub fn track(&mut self, trail: Vec2) {
+        let strength = (trail.mag() / SCENT_SATURATION).min(1.0);
+        if self.target_index.is_some() || strength == 0.0 { return; }
+        let steer = steer_towards(trail, self.max_speed, self.velocity, self.max_force * 0.5);
+        self.acceleration.add_mut(steer.mult(strength));
+    }
 
-        let px = self.position.x;
-        let py = self.position.y;
+    /// Step one tick. `speed` scales top speed for the ground below.
+    pub fn update(&mut self, width: f32, height: f32, speed: f32, obstacles: &Obstacles, flow: &FlowField) -> bool {
+        self.prev_position = self.position;
+        self.energy -= 0.04;
 
         // Avoid obstacles
-        for &(ox, oy) in obstacle_positions {
-            let dx = px - ox;
-            let dy = py - oy;
-            let dsq = dx * dx + dy * dy;
-            if dsq < 900.0 {
-                let m = dsq.sqrt();
-                if m > 0.0 {
-                    self.acceleration.x += dx / m * MAX_FORCE * 3.0;
-                    self.acceleration.y += dy / m * MAX_FORCE * 3.0;
-                }
-            }
+        let (ox, oy) = obstacles.avoidance(self.position.x, self.position.y, self.velocity, AVOID_MARGIN);
+        let om = (ox * ox + oy * oy).sqrt();
+        if om > 0.0 {
+            self.acceleration.x += ox / om * MAX_FORCE * 3.0;
+            self.acceleration.y += oy / om * MAX_FORCE * 3.0;
         }
 
+        if !flow.is_calm() {
+            self.acceleration.add_mut(flow.sample(self.position.x, self.position.y).mult(WIND_MULT));
+        }
         self.velocity.add_mut(self.acceleration);
-        self.velocity.limit_mut(self.max_speed);
+        self.velocity.limit_mut(self.max_speed * speed);
         self.position.add_mut(self.velocity);
         self.acceleration.reset();
+        obstacles.collide(&mut self.position, &mut self.velocity, BODY_RADIUS);
 
         // Wrap edges
         if self.position.x > width { self.position.x = 0.0; }
@@ -148,8 +185,9 @@ impl Predator {
     }
 }
 
+static mut SEED: u32 = 67890;
+
 fn rand_f32() -> f32 {
-    static mut SEED: u32 = 67890;
     unsafe {
         SEED ^= SEED << 13;
         SEED ^= SEED >> 17;
@@ -157,3 +195,85 @@ fn rand_f32() -> f32 {
         (SEED as f32) / (u32::MAX as f32)
     }
 }
+
+pub fn set_seed(seed: u32) {
+    unsafe {
+        SEED = if seed == 0 { 67890 } else { seed };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// All exactly 100px from the predator, so every one is an equal choice
+    const RING: [(f32, f32); 7] = [(-100.0, 0.0), (0.0, 100.0), (60.0, -80.0), (80.0, 60.0), (100.0, 0.0), (0.0, -100.0), (60.0, 80.0)];
+    /// Heading, and the lowest index on `RING` in the predator's cone
+    const HEADINGS: [((f32, f32), usize); 4] = [((2.0, 0.0), 2), ((0.0, -2.0), 2), ((0.0, 2.0), 1), ((-2.0, 0.0), 0)];
+
+    /// Predators at `centres`, each ringed by its own `RING` of prey. The hash
+    /// is filled back to front so cell order never matches index order.
+    fn rings(centres: &[Vec2]) -> (Vec<Prey>, SpatialHash) {
+        let prey: Vec<_> = centres.iter()
+            .flat_map(|c| RING.map(|(dx, dy)| Prey {
+                x: c.x + dx,
+                y: c.y + dy,
+                vx: 0.0,
+                vy: 0.0,
+                energy: 50.0,
+                is_collapsed: false,
+                is_tasty: false,
+                in_shelter: false,
+            }))
+            .collect();
+        let mut hash = SpatialHash::new(50.0);
+        for (i, p) in prey.iter().enumerate().rev() {
+            hash.insert(i, p.x, p.y);
+        }
+        (prey, hash)
+    }
+
+    #[test]
+    fn equidistant_prey_goes_to_the_lowest_index_in_view() {
+        let _rng = crate::rng_lock();
+        let centre = Vec2::new(300.0, 300.0);
+        let (prey, hash) = rings(&[centre]);
+        for ((vx, vy), expected) in HEADINGS {
+            let mut predator = Predator::new(centre.x, centre.y, 0, 0);
+            predator.velocity = Vec2::new(vx, vy);
+            let catch = predator.hunt(&prey, &hash, &mut Vec::new(), Vision::predator(), 1.0, 1.0);
+            assert_eq!((catch, predator.target_index), (None, Some(expected)), "heading ({vx}, {vy})");
+        }
+
+        // The same prey taken out of the cone, or into shelter, is passed over
+        let mut predator = Predator::new(centre.x, centre.y, 0, 0);
+        predator.velocity = Vec2::new(2.0, 0.0);
+        let sheltered: Vec<_> = prey.iter().enumerate().map(|(i, &p)| Prey { in_shelter: i == 2, ..p }).collect();
+        predator.hunt(&sheltered, &hash, &mut Vec::new(), Vision::predator(), 1.0, 1.0);
+        assert_eq!(predator.target_index, Some(3));
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn serial_and_parallel_hunts_pick_the_same_prey() {
+        use rayon::prelude::*;
+
+        let _rng = crate::rng_lock();
+        let centres: Vec<_> = (0..64).map(|i| Vec2::new(300.0 * (i % 8) as f32, 300.0 * (i / 8) as f32)).collect();
+        let (prey, hash) = rings(&centres);
+        let predators: Vec<_> = centres.iter().enumerate().map(|(i, c)| {
+            let ((vx, vy), _) = HEADINGS[i % 4];
+            let mut p = Predator::new(c.x, c.y, i as u32, 0);
+            p.velocity = Vec2::new(vx, vy);
+            p
+        }).collect();
+
+        let (mut a, mut b) = (predators.clone(), predators);
+        a.iter_mut().for_each(|p| { p.hunt(&prey, &hash, &mut Vec::new(), Vision::predator(), 1.0, 1.0); });
+        b.par_iter_mut().for_each_init(Vec::new, |nearby, p| { p.hunt(&prey, &hash, nearby, Vision::predator(), 1.0, 1.0); });
+        for (i, (p, q)) in a.iter().zip(&b).enumerate() {
+            assert_eq!(p.target_index, Some(i * RING.len() + HEADINGS[i % 4].1));
+            assert_eq!(p.target_index, q.target_index);
+        }
+    }
+}
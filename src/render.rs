@@ -0,0 +1,266 @@
+//! Software renderer for headless runs.
+//!
+//! Draws a `World` from the same flat render buffers the browser uses into an
+//! RGBA canvas, which can be saved as PNG frames or appended to an animated GIF.
+
+use std::fs::File;
+use std::io::{self, BufWriter};
+use std::path::Path;
+
+use crate::color::hsl;
+use crate::terrain::Zone;
+use crate::world::World;
+
+/// RGBA8 pixel buffer
+pub struct Canvas {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>,
+}
+
+impl Canvas {
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![0; (width * height * 4) as usize],
+        }
+    }
+
+    pub fn fill(&mut self, rgb: [u8; 3]) {
+        for px in self.pixels.chunks_exact_mut(4) {
+            px.copy_from_slice(&[rgb[0], rgb[1], rgb[2], 255]);
+        }
+    }
+
+    /// Alpha-blend one pixel. Out-of-bounds writes are ignored.
+    #[inline]
+    fn blend(&mut self, x: i32, y: i32, rgb: [u8; 3], alpha: f32) {
+        if x < 0 || y < 0 || x >= self.width as i32 || y >= self.height as i32 { return; }
+        let i = ((y as u32 * self.width + x as u32) * 4) as usize;
+        for (dst, &src) in self.pixels[i..i + 3].iter_mut().zip(&rgb) {
+            *dst = (*dst as f32 + (src as f32 - *dst as f32) * alpha).round() as u8;
+        }
+    }
+
+    /// Blend a colour over the whole canvas
+    pub fn tint(&mut self, rgb: [u8; 3], alpha: f32) {
+        for px in self.pixels.chunks_exact_mut(4) {
+            for c in 0..3 {
+                let dst = px[c] as f32;
+                px[c] = (dst + (rgb[c] as f32 - dst) * alpha).round() as u8;
+            }
+        }
+    }
+
+    pub fn fill_circle(&mut self, cx: f32, cy: f32, r: f32, rgb: [u8; 3], alpha: f32) {
+        let r_sq = r * r;
+        for y in (cy - r).floor() as i32..=(cy + r).ceil() as i32 {
+            for x in (cx - r).floor() as i32..=(cx + r).ceil() as i32 {
+                let dx = x as f32 + 0.5 - cx;
+                let dy = y as f32 + 0.5 - cy;
+                if dx * dx + dy * dy <= r_sq {
+                    self.blend(x, y, rgb, alpha);
+                }
+            }
+        }
+    }
+
+    pub fn stroke_circle(&mut self, cx: f32, cy: f32, r: f32, width: f32, rgb: [u8; 3], alpha: f32) {
+        let outer = r + width * 0.5;
+        let inner = (r - width * 0.5).max(0.0);
+        for y in (cy - outer).floor() as i32..=(cy + outer).ceil() as i32 {
+            for x in (cx - outer).floor() as i32..=(cx + outer).ceil() as i32 {
+                let dx = x as f32 + 0.5 - cx;
+                let dy = y as f32 + 0.5 - cy;
+                let dsq = dx * dx + dy * dy;
+                if dsq <= outer * outer && dsq >= inner * inner {
+                    self.blend(x, y, rgb, alpha);
+                }
+            }
+        }
+    }
+
+    pub fn fill_triangle(&mut self, p: [(f32, f32); 3], rgb: [u8; 3], alpha: f32) {
+        let min_x = p.iter().map(|v| v.0).fold(f32::INFINITY, f32::min).floor() as i32;
+        let max_x = p.iter().map(|v| v.0).fold(f32::NEG_INFINITY, f32::max).ceil() as i32;
+        let min_y = p.iter().map(|v| v.1).fold(f32::INFINITY, f32::min).floor() as i32;
+        let max_y = p.iter().map(|v| v.1).fold(f32::NEG_INFINITY, f32::max).ceil() as i32;
+        let edge = |a: (f32, f32), b: (f32, f32), x: f32, y: f32| (b.0 - a.0) * (y - a.1) - (b.1 - a.1) * (x - a.0);
+        let area = edge(p[0], p[1], p[2].0, p[2].1);
+        if area == 0.0 { return; }
+
+        for y in min_y..=max_y {
+            for x in min_x..=max_x {
+                let (fx, fy) = (x as f32 + 0.5, y as f32 + 0.5);
+                let w0 = edge(p[1], p[2], fx, fy);
+                let w1 = edge(p[2], p[0], fx, fy);
+                let w2 = edge(p[0], p[1], fx, fy);
+                let inside = if area > 0.0 {
+                    w0 >= 0.0 && w1 >= 0.0 && w2 >= 0.0
+                } else {
+                    w0 <= 0.0 && w1 <= 0.0 && w2 <= 0.0
+                };
+                if inside {
+                    self.blend(x, y, rgb, alpha);
+                }
+            }
+        }
+    }
+
+    /// Convex polygon as a triangle fan
+    pub fn fill_polygon(&mut self, points: &[(f32, f32)], rgb: [u8; 3], alpha: f32) {
+        for i in 1..points.len().saturating_sub(1) {
+            self.fill_triangle([points[0], points[i], points[i + 1]], rgb, alpha);
+        }
+    }
+
+    /// Line of `width` px with square ends
+    pub fn stroke_line(&mut self, (x1, y1): (f32, f32), (x2, y2): (f32, f32), width: f32, rgb: [u8; 3], alpha: f32) {
+        let (dx, dy) = (x2 - x1, y2 - y1);
+        let len = (dx * dx + dy * dy).sqrt();
+        if len == 0.0 { return; }
+        let (nx, ny) = (-dy / len * width * 0.5, dx / len * width * 0.5);
+        let quad = [(x1 + nx, y1 + ny), (x2 + nx, y2 + ny), (x2 - nx, y2 - ny), (x1 - nx, y1 - ny)];
+        self.fill_polygon(&quad, rgb, alpha);
+    }
+
+    /// Triangle pointing along (vx, vy), as boids and predators are drawn in the browser
+    fn fill_heading(&mut self, (x, y): (f32, f32), (vx, vy): (f32, f32), length: f32, rgb: [u8; 3], alpha: f32) {
+        let m = (vx * vx + vy * vy).sqrt();
+        let (hx, hy) = if m > 0.0 { (vx / m, vy / m) } else { (1.0, 0.0) };
+        let (nx, ny) = (-hy, hx);
+        let half = length * 0.45;
+        self.fill_triangle(
+            [
+                (x + hx * length, y + hy * length),
+                (x - hx * length * 0.6 + nx * half, y - hy * length * 0.6 + ny * half),
+                (x - hx * length * 0.6 - nx * half, y - hy * length * 0.6 - ny * half),
+            ],
+            rgb,
+            alpha,
+        );
+    }
+}
+
+/// Draw the current state of `world` onto `canvas`
+pub fn draw_world(world: &World, canvas: &mut Canvas) {
+    let light = world.light_level();
+
+    canvas.fill([17, 24, 39]);
+
+    // Meadow is the open background; other ground is washed in its colour
+    let (cw, ch) = world.terrain.cell_size();
+    let cols = world.terrain.cols();
+    for (i, &zone) in world.terrain.zones().iter().enumerate() {
+        if zone == Zone::Meadow { continue; }
+        let (x, y) = ((i % cols) as f32 * cw, (i / cols) as f32 * ch);
+        canvas.fill_polygon(&[(x, y), (x + cw, y), (x + cw, y + ch), (x, y + ch)], zone.rgb(), 0.2);
+    }
+
+    for &(x, y, r) in &world.shelters {
+        canvas.fill_circle(x, y, r, [120, 90, 60], 0.25);
+        canvas.stroke_circle(x, y, r, 2.0, [160, 120, 80], 0.6);
+    }
+
+    for &(x, y, amount) in &world.food_sources {
+        canvas.fill_circle(x, y, 4.0 + amount.max(0.0) / 100.0 * 8.0, [74, 180, 90], 0.85);
+    }
+
+    for o in world.obstacles.as_slice() {
+        canvas.fill_circle(o.position.x, o.position.y, o.radius, [80, 70, 60], 0.9);
+    }
+    for poly in world.obstacles.polygons() {
+        let points: Vec<(f32, f32)> = poly.points().iter().map(|p| (p.x, p.y)).collect();
+        canvas.fill_polygon(&points, [80, 70, 60], 0.9);
+    }
+    for w in world.obstacles.walls() {
+        canvas.stroke_line((w.a.x, w.a.y), (w.b.x, w.b.y), 3.0, [140, 124, 108], 1.0);
+    }
+
+    for bug in world.get_bug_render_data().chunks_exact(5) {
+        canvas.fill_circle(bug[0], bug[1], (bug[3] * 0.6).max(1.0), hsl(bug[2], 0.8, 0.6), 0.9);
+    }
+
+    for b in world.get_boid_render_data().chunks_exact(11) {
+        let (x, y, vx, vy, hue, size_mult, state) = (b[0], b[1], b[2], b[3], b[4], b[7], b[10]);
+        let alpha = if state == 2.0 { 0.5 } else { 0.95 };
+        canvas.fill_heading((x, y), (vx, vy), 6.0 * size_mult, hsl(hue, 0.7, 0.6), alpha);
+    }
+
+    for p in world.get_predator_render_data().chunks_exact(7) {
+        let (x, y, vx, vy, is_leader) = (p[0], p[1], p[2], p[3], p[5]);
+        canvas.fill_heading((x, y), (vx, vy), 10.0, [220, 50, 50], 1.0);
+        if is_leader > 0.0 {
+            canvas.stroke_circle(x, y, 12.0, 1.5, [255, 120, 120], 0.7);
+        }
+    }
+
+    // Night and heavy cloud fall as a dark blue wash
+    canvas.tint([5, 8, 30], (1.0 - light) * 0.45);
+
+    // Firefly glow cuts through the dark
+    for bug in world.get_bug_render_data().chunks_exact(5).filter(|b| b[4] > 0.05) {
+        canvas.fill_circle(bug[0], bug[1], bug[3] * 2.5, [230, 255, 120], bug[4] * 0.5);
+        canvas.fill_circle(bug[0], bug[1], bug[3] * 0.8, [250, 255, 200], bug[4]);
+    }
+}
+
+fn encode_err<E: std::error::Error + Send + Sync + 'static>(e: E) -> io::Error {
+    io::Error::other(e)
+}
+
+/// Read a PNG into an RGBA canvas, e.g. to load a terrain map
+pub fn load_png(path: &Path) -> io::Result<Canvas> {
+    let mut decoder = png::Decoder::new(File::open(path)?);
+    // Palettes, 16-bit and grey images all come out as 8-bit RGB(A)
+    decoder.set_transformations(png::Transformations::normalize_to_color8());
+    let mut reader = decoder.read_info().map_err(encode_err)?;
+    let mut buf = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut buf).map_err(encode_err)?;
+    let mut canvas = Canvas::new(info.width, info.height);
+    let channels = info.color_type.samples();
+    for (dst, src) in canvas.pixels.chunks_exact_mut(4).zip(buf[..info.buffer_size()].chunks_exact(channels)) {
+        let (rgb, alpha) = match channels {
+            1 | 2 => ([src[0]; 3], src.get(1).copied()),
+            _ => ([src[0], src[1], src[2]], src.get(3).copied()),
+        };
+        dst.copy_from_slice(&[rgb[0], rgb[1], rgb[2], alpha.unwrap_or(255)]);
+    }
+    Ok(canvas)
+}
+
+pub fn save_png(canvas: &Canvas, path: &Path) -> io::Result<()> {
+    let file = BufWriter::new(File::create(path)?);
+    let mut encoder = png::Encoder::new(file, canvas.width, canvas.height);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header().map_err(encode_err)?;
+    writer.write_image_data(&canvas.pixels).map_err(encode_err)
+}
+
+/// Animated GIF written one frame at a time
+pub struct GifWriter {
+    encoder: gif::Encoder<BufWriter<File>>,
+    width: u16,
+    height: u16,
+    /// Frame delay in hundredths of a second
+    delay: u16,
+}
+
+impl GifWriter {
+    pub fn create(path: &Path, width: u32, height: u32, delay: u16) -> io::Result<Self> {
+        let (width, height) = (width.min(u16::MAX as u32) as u16, height.min(u16::MAX as u32) as u16);
+        let file = BufWriter::new(File::create(path)?);
+        let mut encoder = gif::Encoder::new(file, width, height, &[]).map_err(encode_err)?;
+        encoder.set_repeat(gif::Repeat::Infinite).map_err(encode_err)?;
+        Ok(Self { encoder, width, height, delay })
+    }
+
+    pub fn add_frame(&mut self, canvas: &Canvas) -> io::Result<()> {
+        let mut pixels = canvas.pixels.clone();
+        let mut frame = gif::Frame::from_rgba_speed(self.width, self.height, &mut pixels, 10);
+        frame.delay = self.delay;
+        self.encoder.write_frame(&frame).map_err(encode_err)
+    }
+}
@@ -0,0 +1,145 @@
+//! Plain-text scenario files for headless runs.
+//!
+//! One command per line, `#` starts a comment:
+//!
+//! ```text
+//! obstacle 300 200 20     # x y [radius]
+//! wall 100 50 100 300     # x1 y1 x2 y2
+//! polygon 600 80 680 80 640 150   # x1 y1 x2 y2 x3 y3 ... (convex hull)
+//! food 450 150            # x y
+//! predator 100 100        # x y
+//! boids 600 200 30        # x y count
+//! shelter 450 300 60      # x y radius
+//! clear_shelters
+//! wind 0.02 0             # x y acceleration per tick
+//! gusts 0.05 200          # strength [scale]
+//! vortex 450 200 120 0.1  # x y radius strength (negative turns anticlockwise)
+//! ```
+
+use crate::obstacle::{Polygon, DEFAULT_RADIUS};
+use crate::vector::Vec2;
+use crate::world::World;
+
+#[derive(Clone)]
+pub enum Command {
+    Obstacle { x: f32, y: f32, radius: f32 },
+    Wall { x1: f32, y1: f32, x2: f32, y2: f32 },
+    Polygon { points: Vec<Vec2> },
+    Food { x: f32, y: f32 },
+    Predator { x: f32, y: f32 },
+    Boids { x: f32, y: f32, count: u32 },
+    Shelter { x: f32, y: f32, radius: f32 },
+    ClearShelters,
+    Wind { x: f32, y: f32 },
+    Gusts { strength: f32, scale: Option<f32> },
+    Vortex { x: f32, y: f32, radius: f32, strength: f32 },
+}
+
+#[derive(Clone, Default)]
+pub struct Scenario {
+    pub commands: Vec<Command>,
+}
+
+impl Scenario {
+    /// Parse scenario text. Errors name the offending line.
+    pub fn parse(text: &str) -> Result<Scenario, String> {
+        let mut commands = Vec::new();
+        for (n, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() { continue; }
+
+            let mut parts = line.split_whitespace();
+            let name = parts.next().unwrap_or("");
+            let args: Vec<f32> = parts
+                .map(|a| a.parse::<f32>().map_err(|_| format!("line {}: bad number '{}'", n + 1, a)))
+                .collect::<Result<_, _>>()?;
+            let arity = |min: usize, max: usize| {
+                if args.len() < min || args.len() > max {
+                    Err(format!("line {}: '{}' takes {} argument(s), got {}", n + 1, name,
+                        if min == max { min.to_string() } else { format!("{}-{}", min, max) }, args.len()))
+                } else {
+                    Ok(())
+                }
+            };
+
+            let command = match name {
+                "obstacle" => {
+                    arity(2, 3)?;
+                    Command::Obstacle { x: args[0], y: args[1], radius: args.get(2).copied().unwrap_or(DEFAULT_RADIUS) }
+                }
+                "wall" => {
+                    arity(4, 4)?;
+                    Command::Wall { x1: args[0], y1: args[1], x2: args[2], y2: args[3] }
+                }
+                "polygon" => {
+                    if args.len() < 6 || !args.len().is_multiple_of(2) {
+                        return Err(format!("line {}: 'polygon' takes 3 or more x y pairs, got {} number(s)", n + 1, args.len()));
+                    }
+                    let points: Vec<Vec2> = args.chunks_exact(2).map(|p| Vec2::new(p[0], p[1])).collect();
+                    if Polygon::new(&points).is_none() {
+                        return Err(format!("line {}: polygon points enclose no area", n + 1));
+                    }
+                    Command::Polygon { points }
+                }
+                "food" => {
+                    arity(2, 2)?;
+                    Command::Food { x: args[0], y: args[1] }
+                }
+                "predator" => {
+                    arity(2, 2)?;
+                    Command::Predator { x: args[0], y: args[1] }
+                }
+                "boids" => {
+                    arity(3, 3)?;
+                    Command::Boids { x: args[0], y: args[1], count: args[2].max(0.0) as u32 }
+                }
+                "shelter" => {
+                    arity(3, 3)?;
+                    Command::Shelter { x: args[0], y: args[1], radius: args[2] }
+                }
+                "clear_shelters" => {
+                    arity(0, 0)?;
+                    Command::ClearShelters
+                }
+                "wind" => {
+                    arity(2, 2)?;
+                    Command::Wind { x: args[0], y: args[1] }
+                }
+                "gusts" => {
+                    arity(1, 2)?;
+                    Command::Gusts { strength: args[0], scale: args.get(1).copied() }
+                }
+                "vortex" => {
+                    arity(4, 4)?;
+                    Command::Vortex { x: args[0], y: args[1], radius: args[2], strength: args[3] }
+                }
+                _ => return Err(format!("line {}: unknown command '{}'", n + 1, name)),
+            };
+            commands.push(command);
+        }
+        Ok(Scenario { commands })
+    }
+
+    pub fn apply(&self, world: &mut World) {
+        for command in &self.commands {
+            match *command {
+                Command::Obstacle { x, y, radius } => world.add_obstacle(x, y, radius),
+                Command::Wall { x1, y1, x2, y2 } => world.add_wall(x1, y1, x2, y2),
+                Command::Polygon { ref points } => {
+                    world.add_polygon(points);
+                }
+                Command::Food { x, y } => world.add_food(x, y),
+                Command::Predator { x, y } => world.add_predator(x, y),
+                Command::Boids { x, y, count } => world.add_boids(x, y, count),
+                Command::Shelter { x, y, radius } => world.shelters.push((x, y, radius)),
+                Command::ClearShelters => world.shelters.clear(),
+                Command::Wind { x, y } => world.flow.wind = Vec2::new(x, y),
+                Command::Gusts { strength, scale } => {
+                    world.flow.gust_strength = strength;
+                    if let Some(scale) = scale { world.flow.gust_scale = scale; }
+                }
+                Command::Vortex { x, y, radius, strength } => world.flow.add_vortex(x, y, radius, strength),
+            }
+        }
+    }
+}
@@ -0,0 +1,214 @@
+//! Scent trails: a grid of smells boids leave behind, which spread into
+//! neighbouring cells and fade each tick.
+//!
+//! Each species marks where it found food and where it was in danger, and
+//! every flying boid leaves a general scent predators can follow. Smells
+//! outlast the tick that laid them, so the flock remembers good and bad
+//! places for a while.
+
+use crate::vector::Vec2;
+
+/// Grid cell size in pixels
+pub const CELL_SIZE: f32 = 20.0;
+/// Number of channels; see `Channel::index`
+pub const CHANNELS: usize = 5;
+/// Share of each cell's scent that spreads to its neighbours per tick
+const DIFFUSION: f32 = 0.1;
+/// Share of scent lost per tick (a half-life of about 70 ticks)
+const EVAPORATION: f32 = 0.01;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Channel {
+    /// "Food found here", by species
+    Food(u8),
+    /// "Danger here", by species
+    Danger(u8),
+    /// Any boid passed by; predators track it
+    Boid,
+}
+
+impl Channel {
+    /// 0 = food (species 0), 1 = danger (species 0), 2 = food (species 1),
+    /// 3 = danger (species 1), 4 = boid
+    pub fn index(self) -> usize {
+        match self {
+            Channel::Food(s) => s.min(1) as usize * 2,
+            Channel::Danger(s) => s.min(1) as usize * 2 + 1,
+            Channel::Boid => 4,
+        }
+    }
+
+    pub fn from_index(index: usize) -> Option<Self> {
+        Some(match index {
+            0 => Channel::Food(0),
+            1 => Channel::Danger(0),
+            2 => Channel::Food(1),
+            3 => Channel::Danger(1),
+            4 => Channel::Boid,
+            _ => return None,
+        })
+    }
+}
+
+pub struct ScentGrid {
+    cols: usize,
+    rows: usize,
+    /// `CHANNELS` layers of `cols * rows` cells, row-major
+    cells: Vec<f32>,
+    scratch: Vec<f32>,
+    /// Share of each cell spreading to its four neighbours per tick, 0..1
+    pub diffusion: f32,
+    /// Share of scent lost per tick, 0..1
+    pub evaporation: f32,
+}
+
+impl ScentGrid {
+    /// An odourless grid covering a `width` by `height` world
+    pub fn new(width: f32, height: f32) -> Self {
+        let cols = (width / CELL_SIZE).ceil().max(1.0) as usize;
+        let rows = (height / CELL_SIZE).ceil().max(1.0) as usize;
+        Self {
+            cols,
+            rows,
+            cells: vec![0.0; CHANNELS * cols * rows],
+            scratch: vec![0.0; cols * rows],
+            diffusion: DIFFUSION,
+            evaporation: EVAPORATION,
+        }
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn layer(&self, channel: Channel) -> &[f32] {
+        let len = self.cols * self.rows;
+        &self.cells[channel.index() * len..][..len]
+    }
+
+    /// Cell under a world point, wrapping like the world does
+    #[inline]
+    fn cell(&self, x: f32, y: f32) -> (usize, usize) {
+        let col = ((x / CELL_SIZE).floor() as i64).rem_euclid(self.cols as i64) as usize;
+        let row = ((y / CELL_SIZE).floor() as i64).rem_euclid(self.rows as i64) as usize;
+        (col, row)
+    }
+
+    pub fn deposit(&mut self, channel: Channel, x: f32, y: f32, amount: f32) {
+        let (col, row) = self.cell(x, y);
+        let i = channel.index() * self.cols * self.rows + row * self.cols + col;
+        self.cells[i] += amount;
+    }
+
+    pub fn sample(&self, channel: Channel, x: f32, y: f32) -> f32 {
+        let (col, row) = self.cell(x, y);
+        self.layer(channel)[row * self.cols + col]
+    }
+
+    /// Direction of increasing scent at a point, as the difference between
+    /// the cells either side per axis. Zero where the smell is flat.
+    pub fn gradient(&self, channel: Channel, x: f32, y: f32) -> Vec2 {
+        let (col, row) = self.cell(x, y);
+        let layer = self.layer(channel);
+        let at = |c: usize, r: usize| layer[r * self.cols + c];
+        let (left, right) = ((col + self.cols - 1) % self.cols, (col + 1) % self.cols);
+        let (up, down) = ((row + self.rows - 1) % self.rows, (row + 1) % self.rows);
+        Vec2::new(
+            (at(right, row) - at(left, row)) * 0.5,
+            (at(col, down) - at(col, up)) * 0.5,
+        )
+    }
+
+    /// Spread and fade every channel by one tick. Scent spreads across the
+    /// world's wrapped edges.
+    pub fn update(&mut self) {
+        let (cols, rows) = (self.cols, self.rows);
+        let len = cols * rows;
+        let keep = (1.0 - self.evaporation).clamp(0.0, 1.0);
+        let spread = self.diffusion.clamp(0.0, 1.0);
+        for layer in self.cells.chunks_exact_mut(len) {
+            self.scratch.copy_from_slice(layer);
+            let old = &self.scratch;
+            for row in 0..rows {
+                let (up, down) = ((row + rows - 1) % rows * cols, (row + 1) % rows * cols);
+                let r = row * cols;
+                for col in 0..cols {
+                    let (left, right) = ((col + cols - 1) % cols, (col + 1) % cols);
+                    let around = old[r + left] + old[r + right] + old[up + col] + old[down + col];
+                    let here = old[r + col];
+                    layer[r + col] = (here + (around * 0.25 - here) * spread) * keep;
+                }
+            }
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.cells.fill(0.0);
+    }
+
+    /// Flat `[cols, rows, value, value, ...]` for one channel, row by row,
+    /// for drawing as a heatmap
+    pub fn render_data(&self, channel: Channel) -> Vec<f32> {
+        let mut data = Vec::with_capacity(2 + self.cols * self.rows);
+        data.push(self.cols as f32);
+        data.push(self.rows as f32);
+        data.extend_from_slice(self.layer(channel));
+        data
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn total(grid: &ScentGrid, channel: Channel) -> f32 {
+        grid.layer(channel).iter().sum()
+    }
+
+    #[test]
+    fn diffusion_conserves_scent_across_edges() {
+        let mut grid = ScentGrid::new(200.0, 100.0);
+        grid.evaporation = 0.0;
+        grid.deposit(Channel::Food(1), 5.0, 5.0, 10.0);
+        for _ in 0..50 {
+            grid.update();
+        }
+        assert!((total(&grid, Channel::Food(1)) - 10.0).abs() < 1e-3);
+        // Spread past the corner to the far side of the world, and nowhere else
+        assert!(grid.sample(Channel::Food(1), 195.0, 95.0) > 0.0);
+        assert!(grid.sample(Channel::Food(1), 5.0, 5.0) < 10.0);
+        assert_eq!(total(&grid, Channel::Food(0)), 0.0);
+        assert_eq!(total(&grid, Channel::Boid), 0.0);
+    }
+
+    #[test]
+    fn evaporation_halves_scent_in_about_seventy_ticks() {
+        let mut grid = ScentGrid::new(200.0, 100.0);
+        grid.diffusion = 0.0;
+        grid.deposit(Channel::Danger(0), 50.0, 50.0, 1.0);
+        grid.update();
+        assert!((grid.sample(Channel::Danger(0), 50.0, 50.0) - 0.99).abs() < 1e-6);
+        for _ in 1..70 {
+            grid.update();
+        }
+        let left = grid.sample(Channel::Danger(0), 50.0, 50.0);
+        assert!((0.48..0.52).contains(&left), "{left}");
+        grid.clear();
+        assert_eq!(total(&grid, Channel::Danger(0)), 0.0);
+    }
+
+    #[test]
+    fn gradient_points_up_the_smell() {
+        let mut grid = ScentGrid::new(200.0, 100.0);
+        grid.deposit(Channel::Boid, 110.0, 50.0, 1.0);
+        let g = grid.gradient(Channel::Boid, 90.0, 50.0);
+        assert!(g.x > 0.0 && g.y == 0.0);
+        let g = grid.gradient(Channel::Boid, 110.0, 70.0);
+        assert!(g.x == 0.0 && g.y < 0.0);
+        assert_eq!(grid.gradient(Channel::Boid, 10.0, 10.0).mag_sq(), 0.0);
+    }
+}
@@ -0,0 +1,197 @@
+//! The seasons and the parameters that change with them.
+//!
+//! `season_time` counts years: each whole unit is one year, split into four
+//! equal seasons starting with spring.
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Season {
+    Spring,
+    Summer,
+    Autumn,
+    Winter,
+}
+
+impl Season {
+    pub const ALL: [Season; 4] = [Season::Spring, Season::Summer, Season::Autumn, Season::Winter];
+
+    pub fn from_time(season_time: f32) -> Self {
+        Self::ALL[((season_time.rem_euclid(1.0) * 4.0) as usize).min(3)]
+    }
+
+    /// How far through its season `season_time` is, 0..1
+    pub fn progress(season_time: f32) -> f32 {
+        (season_time.rem_euclid(1.0) * 4.0).fract()
+    }
+
+    pub fn index(self) -> usize {
+        self as usize
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Season::Spring => "spring",
+            Season::Summer => "summer",
+            Season::Autumn => "autumn",
+            Season::Winter => "winter",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|s| s.name() == name)
+    }
+}
+
+/// Everything that varies by season
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SeasonParams {
+    /// Chance per tick of a bug spawning
+    pub bug_rate: f32,
+    pub max_bugs: usize,
+    /// Chance per daytime tick of a food source appearing
+    pub food_chance: f32,
+    /// Day/night cycle length; 1 = about 4200 ticks, 2 = twice as long
+    pub day_length: f32,
+    /// Scales the birth chance of hosts that breed boids
+    pub breeding_mult: f32,
+    /// Scales predators' hunting range and force
+    pub predator_aggression: f32,
+}
+
+impl SeasonParams {
+    /// Names accepted by `get` and `set`, in the order `WasmWorld` reports them
+    pub const NAMES: [&'static str; 6] =
+        ["bug_rate", "max_bugs", "food_chance", "day_length", "breeding_mult", "predator_aggression"];
+
+    pub fn get(&self, name: &str) -> Option<f32> {
+        Some(match name {
+            "bug_rate" => self.bug_rate,
+            "max_bugs" => self.max_bugs as f32,
+            "food_chance" => self.food_chance,
+            "day_length" => self.day_length,
+            "breeding_mult" => self.breeding_mult,
+            "predator_aggression" => self.predator_aggression,
+            _ => return None,
+        })
+    }
+
+    /// Set a parameter by name. Returns false for an unknown name.
+    pub fn set(&mut self, name: &str, value: f32) -> bool {
+        let value = value.max(0.0);
+        match name {
+            "bug_rate" => self.bug_rate = value,
+            "max_bugs" => self.max_bugs = value as usize,
+            "food_chance" => self.food_chance = value,
+            // Zero would stop the clock
+            "day_length" => self.day_length = value.max(0.01),
+            "breeding_mult" => self.breeding_mult = value,
+            "predator_aggression" => self.predator_aggression = value,
+            _ => return false,
+        }
+        true
+    }
+}
+
+/// Parameters for each season: spring broods, busy summers, lean winters
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SeasonTable {
+    pub params: [SeasonParams; 4],
+}
+
+impl SeasonTable {
+    pub fn get(&self, season: Season) -> &SeasonParams {
+        &self.params[season.index()]
+    }
+
+    pub fn get_mut(&mut self, season: Season) -> &mut SeasonParams {
+        &mut self.params[season.index()]
+    }
+}
+
+impl Default for SeasonTable {
+    fn default() -> Self {
+        let base = SeasonParams {
+            bug_rate: 0.08,
+            max_bugs: 40,
+            food_chance: 0.0015,
+            day_length: 1.0,
+            breeding_mult: 1.0,
+            predator_aggression: 1.0,
+        };
+        Self {
+            params: [
+                SeasonParams { breeding_mult: 1.5, ..base },
+                SeasonParams { bug_rate: 0.15, max_bugs: 60, food_chance: 0.003, ..base },
+                base,
+                SeasonParams { bug_rate: 0.03, max_bugs: 20, food_chance: 0.0003, breeding_mult: 0.3, ..base },
+            ],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn season_boundaries_and_progress() {
+        assert_eq!(Season::from_time(0.0), Season::Spring);
+        assert_eq!(Season::from_time(0.2499), Season::Spring);
+        assert_eq!(Season::from_time(0.25), Season::Summer);
+        assert_eq!(Season::from_time(0.75), Season::Winter);
+        // Years wrap, including before the start
+        assert_eq!(Season::from_time(3.5), Season::Autumn);
+        assert_eq!(Season::from_time(-0.1), Season::Winter);
+        assert!((Season::progress(1.375) - 0.5).abs() < 1e-5);
+        for season in Season::ALL {
+            assert_eq!(Season::from_name(season.name()), Some(season));
+        }
+        assert_eq!(Season::from_name("monsoon"), None);
+    }
+
+    #[test]
+    fn default_table_differs_by_season() {
+        let table = SeasonTable::default();
+        let spring = table.get(Season::Spring);
+        let summer = table.get(Season::Summer);
+        let winter = table.get(Season::Winter);
+        assert!(spring.breeding_mult > table.get(Season::Autumn).breeding_mult);
+        assert!(winter.breeding_mult < 1.0);
+        assert!(summer.bug_rate > spring.bug_rate && spring.bug_rate > winter.bug_rate);
+        assert!(summer.max_bugs > winter.max_bugs);
+        assert_eq!(summer.predator_aggression, winter.predator_aggression);
+    }
+
+    #[test]
+    fn world_follows_the_table_across_a_season_change() {
+        let _rng = crate::rng_lock();
+        let mut world = crate::World::new(400.0, 300.0, 0);
+        world.seasons.get_mut(Season::Summer).set("breeding_mult", 0.5);
+        world.season_time = 0.25 - 0.0002 * 2.5;
+        assert_eq!(world.season(), Season::Spring);
+        assert_eq!(world.breeding_mult(), 1.5);
+        for _ in 0..3 {
+            world.tick(0.0, 0.0, 0, 0.0);
+        }
+        assert_eq!(world.season(), Season::Summer);
+        assert_eq!(world.breeding_mult(), 0.5);
+    }
+
+    #[test]
+    fn params_by_name() {
+        let mut table = SeasonTable::default();
+        let params = table.get_mut(Season::Winter);
+        for name in SeasonParams::NAMES {
+            assert!(params.get(name).is_some(), "{name} not readable");
+            assert!(params.set(name, 2.0), "{name} not settable");
+            assert_eq!(params.get(name), Some(2.0));
+        }
+        assert!(!params.set("nonsense", 1.0));
+        assert_eq!(params.get("nonsense"), None);
+        params.set("day_length", 0.0);
+        assert_eq!(params.day_length, 0.01);
+        params.set("breeding_mult", -1.0);
+        assert_eq!(params.breeding_mult, 0.0);
+        // Only the season that was changed
+        assert_eq!(table.get(Season::Autumn).breeding_mult, 1.0);
+    }
+}
@@ -0,0 +1,206 @@
+//! Four-lane f32 vectors for the flocking kernel.
+//!
+//! On wasm32 built with `-C target-feature=+simd128` these map directly onto
+//! `v128` instructions. Everywhere else they are plain arrays, which keeps the
+//! kernel portable and lets the native build auto-vectorize where it can.
+
+#[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+mod lanes {
+    use core::arch::wasm32::*;
+
+    #[derive(Clone, Copy)]
+    pub struct F32x4(v128);
+
+    #[derive(Clone, Copy)]
+    pub struct Mask4(v128);
+
+    impl F32x4 {
+        #[inline]
+        pub fn new(a: f32, b: f32, c: f32, d: f32) -> Self {
+            Self(f32x4(a, b, c, d))
+        }
+
+        #[inline]
+        pub fn splat(v: f32) -> Self {
+            Self(f32x4_splat(v))
+        }
+
+        #[inline]
+        pub fn from_array(a: [f32; 4]) -> Self {
+            Self(f32x4(a[0], a[1], a[2], a[3]))
+        }
+
+        #[inline]
+        pub fn add(self, other: Self) -> Self {
+            Self(f32x4_add(self.0, other.0))
+        }
+
+        #[inline]
+        pub fn sub(self, other: Self) -> Self {
+            Self(f32x4_sub(self.0, other.0))
+        }
+
+        #[inline]
+        pub fn mult(self, other: Self) -> Self {
+            Self(f32x4_mul(self.0, other.0))
+        }
+
+        #[inline]
+        pub fn div(self, other: Self) -> Self {
+            Self(f32x4_div(self.0, other.0))
+        }
+
+        #[inline]
+        pub fn sqrt(self) -> Self {
+            Self(f32x4_sqrt(self.0))
+        }
+
+        #[inline]
+        pub fn lt(self, other: Self) -> Mask4 {
+            Mask4(f32x4_lt(self.0, other.0))
+        }
+
+        #[inline]
+        pub fn gt(self, other: Self) -> Mask4 {
+            Mask4(f32x4_gt(self.0, other.0))
+        }
+
+        #[inline]
+        pub fn to_array(self) -> [f32; 4] {
+            [
+                f32x4_extract_lane::<0>(self.0),
+                f32x4_extract_lane::<1>(self.0),
+                f32x4_extract_lane::<2>(self.0),
+                f32x4_extract_lane::<3>(self.0),
+            ]
+        }
+
+        #[inline]
+        pub fn sum(self) -> f32 {
+            f32x4_extract_lane::<0>(self.0)
+                + f32x4_extract_lane::<1>(self.0)
+                + f32x4_extract_lane::<2>(self.0)
+                + f32x4_extract_lane::<3>(self.0)
+        }
+    }
+
+    impl Mask4 {
+        #[inline]
+        pub fn and(self, other: Self) -> Self {
+            Self(v128_and(self.0, other.0))
+        }
+
+        /// Lane-wise `if mask { a } else { b }`
+        #[inline]
+        pub fn select(self, a: F32x4, b: F32x4) -> F32x4 {
+            F32x4(v128_bitselect(a.0, b.0, self.0))
+        }
+    }
+}
+
+#[cfg(not(all(target_arch = "wasm32", target_feature = "simd128")))]
+mod lanes {
+    #[derive(Clone, Copy)]
+    pub struct F32x4([f32; 4]);
+
+    #[derive(Clone, Copy)]
+    pub struct Mask4([bool; 4]);
+
+    impl F32x4 {
+        #[inline]
+        pub fn new(a: f32, b: f32, c: f32, d: f32) -> Self {
+            Self([a, b, c, d])
+        }
+
+        #[inline]
+        pub fn splat(v: f32) -> Self {
+            Self([v; 4])
+        }
+
+        #[inline]
+        pub fn from_array(a: [f32; 4]) -> Self {
+            Self(a)
+        }
+
+        #[inline]
+        fn zip(self, other: Self, f: impl Fn(f32, f32) -> f32) -> Self {
+            let (a, b) = (self.0, other.0);
+            Self([f(a[0], b[0]), f(a[1], b[1]), f(a[2], b[2]), f(a[3], b[3])])
+        }
+
+        #[inline]
+        fn cmp(self, other: Self, f: impl Fn(f32, f32) -> bool) -> Mask4 {
+            let (a, b) = (self.0, other.0);
+            Mask4([f(a[0], b[0]), f(a[1], b[1]), f(a[2], b[2]), f(a[3], b[3])])
+        }
+
+        #[inline]
+        pub fn add(self, other: Self) -> Self {
+            self.zip(other, |a, b| a + b)
+        }
+
+        #[inline]
+        pub fn sub(self, other: Self) -> Self {
+            self.zip(other, |a, b| a - b)
+        }
+
+        #[inline]
+        pub fn mult(self, other: Self) -> Self {
+            self.zip(other, |a, b| a * b)
+        }
+
+        #[inline]
+        pub fn div(self, other: Self) -> Self {
+            self.zip(other, |a, b| a / b)
+        }
+
+        #[inline]
+        pub fn sqrt(self) -> Self {
+            let a = self.0;
+            Self([a[0].sqrt(), a[1].sqrt(), a[2].sqrt(), a[3].sqrt()])
+        }
+
+        #[inline]
+        pub fn lt(self, other: Self) -> Mask4 {
+            self.cmp(other, |a, b| a < b)
+        }
+
+        #[inline]
+        pub fn gt(self, other: Self) -> Mask4 {
+            self.cmp(other, |a, b| a > b)
+        }
+
+        #[inline]
+        pub fn to_array(self) -> [f32; 4] {
+            self.0
+        }
+
+        #[inline]
+        pub fn sum(self) -> f32 {
+            self.0[0] + self.0[1] + self.0[2] + self.0[3]
+        }
+    }
+
+    impl Mask4 {
+        #[inline]
+        pub fn and(self, other: Self) -> Self {
+            let (a, b) = (self.0, other.0);
+            Self([a[0] && b[0], a[1] && b[1], a[2] && b[2], a[3] && b[3]])
+        }
+
+        /// Lane-wise `if mask { a } else { b }`
+        #[inline]
+        pub fn select(self, a: F32x4, b: F32x4) -> F32x4 {
+            let m = self.0;
+            let (a, b) = (a.0, b.0);
+            F32x4([
+                if m[0] { a[0] } else { b[0] },
+                if m[1] { a[1] } else { b[1] },
+                if m[2] { a[2] } else { b[2] },
+                if m[3] { a[3] } else { b[3] },
+            ])
+        }
+    }
+}
+
+pub use lanes::F32x4;
@@ -14,8 +14,9 @@ impl SpatialHash {
         }
     }
 
+    /// Cell containing a point
     #[inline]
-    fn get_key(&self, x: f32, y: f32) -> (i32, i32) {
+    pub fn get_key(&self, x: f32, y: f32) -> (i32, i32) {
         (
             (x / self.cell_size).floor() as i32,
             (y / self.cell_size).floor() as i32,
@@ -28,7 +29,18 @@ impl SpatialHash {
 
     pub fn insert(&mut self, index: usize, x: f32, y: f32) {
         let key = self.get_key(x, y);
-        self.cells.entry(key).or_insert_with(Vec::new).push(index);
+        self.cells.entry(key).or_default().push(index);
+    }
+
+    /// Insert an entity spanning a box into every cell the box touches
+    pub fn insert_box(&mut self, index: usize, min_x: f32, min_y: f32, max_x: f32, max_y: f32) {
+        let (x0, y0) = self.get_key(min_x, min_y);
+        let (x1, y1) = self.get_key(max_x, max_y);
+        for cx in x0..=x1 {
+            for cy in y0..=y1 {
+                self.cells.entry((cx, cy)).or_default().push(index);
+            }
+        }
     }
 
     /// Returns indices of entities in nearby cells
@@ -61,4 +73,44 @@ impl SpatialHash {
             }
         }
     }
+
+    /// Fills `buffer` with indices from every cell that could hold an entity within `dist`
+    pub fn get_within_into(&self, x: f32, y: f32, dist: f32, buffer: &mut Vec<usize>) {
+        let radius = (dist / self.cell_size).ceil() as i32;
+        self.get_nearby_into(x, y, radius, buffer);
+    }
+
+    /// Calls `f` for every index in nearby cells without collecting them
+    #[inline]
+    pub fn for_each_nearby(&self, x: f32, y: f32, radius: i32, mut f: impl FnMut(usize)) {
+        let (cx, cy) = self.get_key(x, y);
+
+        for dx in -radius..=radius {
+            for dy in -radius..=radius {
+                if let Some(indices) = self.cells.get(&(cx + dx, cy + dy)) {
+                    for &i in indices {
+                        f(i);
+                    }
+                }
+            }
+        }
+    }
+
+    /// `for_each_nearby`, also passing the cell each index was found in, so
+    /// entities stored in several cells can be told apart from repeats
+    #[inline]
+    pub fn for_each_nearby_cell(&self, x: f32, y: f32, radius: i32, mut f: impl FnMut((i32, i32), usize)) {
+        let (cx, cy) = self.get_key(x, y);
+
+        for dx in -radius..=radius {
+            for dy in -radius..=radius {
+                let key = (cx + dx, cy + dy);
+                if let Some(indices) = self.cells.get(&key) {
+                    for &i in indices {
+                        f(key, i);
+                    }
+                }
+            }
+        }
+    }
 }
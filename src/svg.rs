@@ -0,0 +1,244 @@
+//! Vector snapshot of a world as a standalone SVG document.
+
+use std::fmt::Write;
+
+use crate::boid::BoidState;
+use crate::color::{hex, hsl};
+use crate::spatial::SpatialHash;
+use crate::terrain::Zone;
+use crate::world::World;
+
+/// Distance within which flock-mates are joined by a neighbour link (the alignment radius)
+const LINK_RADIUS: f32 = 50.0;
+
+#[derive(Clone, Copy, Default)]
+pub struct SvgOptions {
+    /// Draw each boid's and predator's velocity as a short line
+    pub velocity_vectors: bool,
+    /// Draw lines between flock-mates within alignment range
+    pub neighbour_links: bool,
+}
+
+/// Triangle pointing along the velocity, `length` from centre to nose
+fn heading_points(x: f32, y: f32, vx: f32, vy: f32, length: f32) -> String {
+    let m = (vx * vx + vy * vy).sqrt();
+    let (hx, hy) = if m > 0.0 { (vx / m, vy / m) } else { (1.0, 0.0) };
+    let (nx, ny) = (-hy, hx);
+    let half = length * 0.45;
+    format!(
+        "{:.1},{:.1} {:.1},{:.1} {:.1},{:.1}",
+        x + hx * length, y + hy * length,
+        x - hx * length * 0.6 + nx * half, y - hy * length * 0.6 + ny * half,
+        x - hx * length * 0.6 - nx * half, y - hy * length * 0.6 - ny * half,
+    )
+}
+
+pub fn export_svg(world: &World, options: SvgOptions) -> String {
+    let (w, h) = (world.width, world.height);
+    let light = world.light_level();
+    let mut out = String::with_capacity(256 + world.boids.len() * 96);
+
+    // Writing to a String cannot fail
+    let _ = writeln!(out, r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}">"#);
+    let _ = writeln!(out, r##"<rect width="{w}" height="{h}" fill="#111827"/>"##);
+
+    let _ = writeln!(out, r#"<g id="terrain" fill-opacity="0.2">"#);
+    let (cw, ch) = world.terrain.cell_size();
+    let cols = world.terrain.cols();
+    for (i, &zone) in world.terrain.zones().iter().enumerate() {
+        if zone == Zone::Meadow { continue; }
+        let (x, y) = ((i % cols) as f32 * cw, (i / cols) as f32 * ch);
+        let _ = writeln!(out, r#"<rect x="{x:.1}" y="{y:.1}" width="{cw:.1}" height="{ch:.1}" fill="{}"/>"#, hex(zone.rgb()));
+    }
+    let _ = writeln!(out, "</g>");
+
+    let _ = writeln!(out, r#"<g id="shelters">"#);
+    for &(x, y, r) in &world.shelters {
+        let _ = writeln!(out, r##"<circle cx="{x:.1}" cy="{y:.1}" r="{r:.1}" fill="#785a3c" fill-opacity="0.25" stroke="#a07850" stroke-width="2"/>"##);
+    }
+    let _ = writeln!(out, "</g>");
+
+    let _ = writeln!(out, r#"<g id="food">"#);
+    for &(x, y, amount) in &world.food_sources {
+        let r = 4.0 + amount.max(0.0) / 100.0 * 8.0;
+        let _ = writeln!(out, r##"<circle cx="{x:.1}" cy="{y:.1}" r="{r:.1}" fill="#4ab45a"/>"##);
+    }
+    let _ = writeln!(out, "</g>");
+
+    let _ = writeln!(out, r#"<g id="obstacles">"#);
+    for o in world.obstacles.as_slice() {
+        let _ = writeln!(out, r##"<circle cx="{:.1}" cy="{:.1}" r="{:.1}" fill="#50463c"/>"##, o.position.x, o.position.y, o.radius);
+    }
+    for poly in world.obstacles.polygons() {
+        let points: Vec<String> = poly.points().iter().map(|p| format!("{:.1},{:.1}", p.x, p.y)).collect();
+        let _ = writeln!(out, r##"<polygon points="{}" fill="#50463c"/>"##, points.join(" "));
+    }
+    for w in world.obstacles.walls() {
+        let _ = writeln!(out, r##"<line x1="{:.1}" y1="{:.1}" x2="{:.1}" y2="{:.1}" stroke="#8c7c6c" stroke-width="3" stroke-linecap="round"/>"##,
+            w.a.x, w.a.y, w.b.x, w.b.y);
+    }
+    let _ = writeln!(out, "</g>");
+
+    if options.neighbour_links {
+        let mut hash = SpatialHash::new(LINK_RADIUS);
+        for (i, b) in world.boids.iter().enumerate() {
+            hash.insert(i, b.position.x, b.position.y);
+        }
+        let mut nearby = Vec::new();
+        let _ = writeln!(out, r##"<g id="links" stroke="#ffffff" stroke-opacity="0.15" stroke-width="0.6">"##);
+        for (i, a) in world.boids.iter().enumerate() {
+            if a.state != BoidState::Normal { continue; }
+            hash.get_nearby_into(a.position.x, a.position.y, 1, &mut nearby);
+            for &j in &nearby {
+                let b = &world.boids[j];
+                if j <= i || b.state != BoidState::Normal { continue; }
+                let same_flock = a.is_hybrid || b.is_hybrid || a.species == b.species;
+                if same_flock && a.position.dist_sq(b.position) < LINK_RADIUS * LINK_RADIUS {
+                    let _ = writeln!(out, r#"<line x1="{:.1}" y1="{:.1}" x2="{:.1}" y2="{:.1}"/>"#,
+                        a.position.x, a.position.y, b.position.x, b.position.y);
+                }
+            }
+        }
+        let _ = writeln!(out, "</g>");
+    }
+
+    let _ = writeln!(out, r#"<g id="bugs">"#);
+    for bug in &world.bugs {
+        let _ = writeln!(out, r#"<circle cx="{:.1}" cy="{:.1}" r="{:.1}" fill="{}"/>"#,
+            bug.position.x, bug.position.y, (bug.size * 0.6).max(1.0), hex(hsl(bug.hue, 0.8, 0.6)));
+        if bug.brightness > 0.05 {
+            let _ = writeln!(out, r##"<circle cx="{:.1}" cy="{:.1}" r="{:.1}" fill="#e6ff78" fill-opacity="{:.2}"/>"##,
+                bug.position.x, bug.position.y, bug.size * 2.5, bug.brightness * 0.5);
+        }
+    }
+    let _ = writeln!(out, "</g>");
+
+    let _ = writeln!(out, r#"<g id="boids">"#);
+    for b in &world.boids {
+        let (x, y, vx, vy) = (b.position.x, b.position.y, b.velocity.x, b.velocity.y);
+        let opacity = if b.state == BoidState::Collapsed { 0.5 } else { 0.95 };
+        let _ = writeln!(out, r#"<polygon points="{}" fill="{}" fill-opacity="{}"/>"#,
+            heading_points(x, y, vx, vy, 6.0 * b.size_mult), hex(hsl(b.hue, 0.7, 0.6)), opacity);
+    }
+    let _ = writeln!(out, "</g>");
+
+    let _ = writeln!(out, r#"<g id="predators">"#);
+    for p in &world.predators {
+        let (x, y, vx, vy) = (p.position.x, p.position.y, p.velocity.x, p.velocity.y);
+        let _ = writeln!(out, r##"<polygon points="{}" fill="#dc3232"/>"##, heading_points(x, y, vx, vy, 10.0));
+        if p.is_leader {
+            let _ = writeln!(out, r##"<circle cx="{x:.1}" cy="{y:.1}" r="12" fill="none" stroke="#ff7878" stroke-width="1.5"/>"##);
+        }
+    }
+    let _ = writeln!(out, "</g>");
+
+    if options.velocity_vectors {
+        // Scaled so a boid at max speed (4px/tick) gets a 20px arrow
+        let _ = writeln!(out, r##"<g id="velocities" stroke="#e5e7eb" stroke-opacity="0.7" stroke-width="0.8">"##);
+        let entities = world.boids.iter().map(|b| (b.position, b.velocity))
+            .chain(world.predators.iter().map(|p| (p.position, p.velocity)));
+        for (pos, vel) in entities {
+            let _ = writeln!(out, r#"<line x1="{:.1}" y1="{:.1}" x2="{:.1}" y2="{:.1}"/>"#,
+                pos.x, pos.y, pos.x + vel.x * 5.0, pos.y + vel.y * 5.0);
+        }
+        let _ = writeln!(out, "</g>");
+    }
+
+    // Night and heavy cloud fall as a dark blue wash
+    let night = (1.0 - light) * 0.45;
+    if night > 0.0 {
+        let _ = writeln!(out, r##"<rect width="{w}" height="{h}" fill="#05081e" fill-opacity="{night:.3}"/>"##);
+    }
+
+    out.push_str("</svg>\n");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bug::Bug;
+    use crate::weather::Weather;
+
+    /// Checks tags nest and close properly and returns each group's id with
+    /// how many elements sit directly inside it, in document order
+    fn groups(svg: &str) -> Vec<(String, usize)> {
+        let mut open: Vec<(String, Option<usize>)> = Vec::new();
+        let mut groups: Vec<(String, usize)> = Vec::new();
+        let mut rest = svg;
+        while let Some(start) = rest.find('<') {
+            let end = start + rest[start..].find('>').expect("unclosed tag");
+            let tag = &rest[start + 1..end];
+            rest = &rest[end + 1..];
+            assert_eq!(tag.matches('"').count() % 2, 0, "unbalanced quotes in <{tag}>");
+            if let Some(name) = tag.strip_prefix('/') {
+                let (opened, _) = open.pop().expect("close without open");
+                assert_eq!(opened, name);
+                continue;
+            }
+            if let Some(&(_, Some(g))) = open.last() {
+                groups[g].1 += 1;
+            }
+            if tag.ends_with('/') { continue; }
+            let name = tag.split_whitespace().next().unwrap_or_default().to_string();
+            let group = (name == "g").then(|| {
+                let id = tag.split("id=\"").nth(1).and_then(|s| s.split('"').next()).unwrap_or_default();
+                groups.push((id.to_string(), 0));
+                groups.len() - 1
+            });
+            open.push((name, group));
+        }
+        assert!(open.is_empty(), "unclosed {open:?}");
+        assert!(rest.trim().is_empty());
+        groups
+    }
+
+    #[test]
+    fn small_world_exports_every_element() {
+        let _rng = crate::rng_lock();
+        let mut world = World::new(200.0, 160.0, 0);
+        world.obstacles.clear();
+        world.obstacles.add(150.0, 40.0, 10.0);
+        world.obstacles.add_wall(10.0, 10.0, 60.0, 10.0);
+        world.shelters = vec![(100.0, 120.0, 20.0)];
+        world.food_sources.push((30.0, 100.0, 50.0));
+        world.weather.current = Weather::Clear;
+        world.set_time_of_day(0.5);
+        // Two flock-mates in link range and a stranger of the other species
+        world.add_boid(50.0, 50.0, 0);
+        world.add_boid(70.0, 60.0, 0);
+        world.add_boid(60.0, 70.0, 1);
+        world.add_predator(180.0, 140.0);
+        world.bugs.push(Bug::new(120.0, 80.0));
+        world.bugs[0].brightness = 0.0;
+        world.bugs.push(Bug::firefly(130.0, 90.0));
+        world.bugs[1].brightness = 1.0;
+
+        let terrain = world.terrain.zones().iter().filter(|&&z| z != Zone::Meadow).count();
+        assert!(terrain > 0);
+        let all = SvgOptions { velocity_vectors: true, neighbour_links: true };
+        let svg = export_svg(&world, all);
+        assert!(svg.starts_with(r#"<svg xmlns="http://www.w3.org/2000/svg" width="200" height="160""#));
+        let expected = [
+            ("terrain", terrain),
+            ("shelters", 1),
+            ("food", 1),
+            ("obstacles", 2),
+            ("links", 1),
+            ("bugs", 3),
+            ("boids", 3),
+            ("predators", 1),
+            ("velocities", 4),
+        ];
+        let found = groups(&svg);
+        assert_eq!(found, expected.map(|(id, n)| (id.to_string(), n)));
+        // Noon under clear skies has no night wash
+        assert_eq!(svg.matches("<rect").count(), 1 + terrain);
+
+        world.set_time_of_day(0.0);
+        let svg = export_svg(&world, SvgOptions::default());
+        let ids: Vec<_> = groups(&svg).into_iter().map(|(id, _)| id).collect();
+        assert!(!ids.iter().any(|id| id == "links" || id == "velocities"));
+        assert_eq!(svg.matches("<rect").count(), 2 + terrain);
+    }
+}
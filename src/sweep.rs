@@ -0,0 +1,212 @@
+//! Batch experiments over a grid of world parameters.
+//!
+//! Every combination of predator count × bug rate × shelter radius is run
+//! once per seed, and the runs are summarised into one row per combination.
+
+use std::str::FromStr;
+
+use crate::world::World;
+
+#[derive(Clone)]
+pub struct Sweep {
+    pub width: f32,
+    pub height: f32,
+    pub boids: u32,
+    pub ticks: u32,
+    pub predators: Vec<u32>,
+    pub bug_mult: Vec<f32>,
+    pub shelter_radius: Vec<f32>,
+    pub seeds: Vec<u32>,
+}
+
+/// One point in the parameter grid
+#[derive(Clone, Copy)]
+pub struct Config {
+    pub predators: u32,
+    pub bug_mult: f32,
+    pub shelter_radius: f32,
+}
+
+/// Outcome of a single seeded run
+#[derive(Clone, Copy)]
+pub struct RunResult {
+    /// First tick with no boids left, if it happened
+    pub extinction_tick: Option<u32>,
+    pub mean_population: f32,
+}
+
+/// A configuration's runs aggregated across seeds
+#[derive(Clone, Copy)]
+pub struct Summary {
+    pub config: Config,
+    pub runs: u32,
+    pub extinctions: u32,
+    /// Mean extinction tick over the runs that went extinct
+    pub mean_extinction_tick: Option<f32>,
+    pub mean_population: f32,
+}
+
+impl Sweep {
+    pub fn configs(&self) -> Vec<Config> {
+        let mut configs = Vec::new();
+        for &predators in &self.predators {
+            for &bug_mult in &self.bug_mult {
+                for &shelter_radius in &self.shelter_radius {
+                    configs.push(Config { predators, bug_mult, shelter_radius });
+                }
+            }
+        }
+        configs
+    }
+
+    /// Run one configuration with a fixed seed until `ticks` or extinction
+    pub fn run_one(&self, config: Config, seed: u32) -> RunResult {
+        crate::set_seed(seed);
+        let mut world = World::new(self.width, self.height, self.boids);
+        world.bug_mult = config.bug_mult;
+        for shelter in &mut world.shelters {
+            shelter.2 = config.shelter_radius;
+        }
+        world.add_random_predators(config.predators);
+
+        let mut population_sum = world.boids.len() as f64;
+        let mut samples = 1u32;
+        let mut extinction_tick = None;
+
+        for t in 1..=self.ticks {
+            world.tick(0.0, 0.0, 0, 0.0);
+            population_sum += world.boids.len() as f64;
+            samples += 1;
+            if world.boids.is_empty() {
+                extinction_tick = Some(t);
+                // Nothing left to measure; count the remaining ticks as empty
+                samples += self.ticks - t;
+                break;
+            }
+        }
+
+        RunResult {
+            extinction_tick,
+            mean_population: (population_sum / samples as f64) as f32,
+        }
+    }
+
+    /// Run a configuration once per seed and aggregate
+    pub fn run(&self, config: Config) -> Summary {
+        let results: Vec<_> = self.seeds.iter().map(|&seed| self.run_one(config, seed)).collect();
+        let runs = results.len() as u32;
+        let n = runs.max(1) as f32;
+        let extinct: Vec<u32> = results.iter().filter_map(|r| r.extinction_tick).collect();
+
+        Summary {
+            config,
+            runs,
+            extinctions: extinct.len() as u32,
+            mean_extinction_tick: if extinct.is_empty() {
+                None
+            } else {
+                Some(extinct.iter().map(|&t| t as f32).sum::<f32>() / extinct.len() as f32)
+            },
+            mean_population: results.iter().map(|r| r.mean_population).sum::<f32>() / n,
+        }
+    }
+}
+
+impl Summary {
+    pub const CSV_HEADER: &'static str = "predators,bug_mult,shelter_radius,runs,extinctions,\
+mean_extinction_tick,mean_population";
+
+    /// One CSV row. Runs that never went extinct leave `mean_extinction_tick` empty.
+    pub fn to_csv(&self) -> String {
+        format!(
+            "{},{},{},{},{},{},{:.3}",
+            self.config.predators,
+            self.config.bug_mult,
+            self.config.shelter_radius,
+            self.runs,
+            self.extinctions,
+            self.mean_extinction_tick.map(|t| format!("{:.1}", t)).unwrap_or_default(),
+            self.mean_population,
+        )
+    }
+}
+
+/// Parse a list of sweep values: `3`, `1,2,5`, `1..10` (inclusive) or
+/// `0.5..2:0.5` (inclusive with step).
+pub fn parse_values<T>(spec: &str) -> Result<Vec<T>, String>
+where
+    T: FromStr + Copy + Into<f64>,
+{
+    let bad = |s: &str| format!("bad value '{}' in '{}'", s, spec);
+    let mut values = Vec::new();
+    for part in spec.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+        let Some((start, rest)) = part.split_once("..") else {
+            values.push(part.parse::<T>().map_err(|_| bad(part))?);
+            continue;
+        };
+        let (end, step) = match rest.split_once(':') {
+            Some((end, step)) => (end, Some(step)),
+            None => (rest, None),
+        };
+        let start_v = start.parse::<T>().map_err(|_| bad(start))?;
+        let end_v = end.parse::<T>().map_err(|_| bad(end))?;
+        let step_v: f64 = match step {
+            Some(s) => s.parse::<T>().map_err(|_| bad(s))?.into(),
+            None => 1.0,
+        };
+        if step_v <= 0.0 {
+            return Err(format!("step must be positive in '{}'", part));
+        }
+
+        let (a, b): (f64, f64) = (start_v.into(), end_v.into());
+        let count = ((b - a) / step_v + 1e-9).floor();
+        if count < 0.0 {
+            return Err(format!("empty range '{}'", part));
+        }
+        for i in 0..=count as u32 {
+            let v = a + i as f64 * step_v;
+            // Round-trip through text so both integer and float targets parse
+            let text = format!("{}", (v * 1e6).round() / 1e6);
+            values.push(text.parse::<T>().map_err(|_| bad(&text))?);
+        }
+    }
+    if values.is_empty() {
+        return Err(format!("no values in '{}'", spec));
+    }
+    Ok(values)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn summary_agrees_with_its_runs() {
+        let _rng = crate::rng_lock();
+        let sweep = Sweep {
+            width: 300.0,
+            height: 200.0,
+            boids: 3,
+            ticks: 400,
+            predators: vec![0, 8],
+            bug_mult: vec![1.0],
+            shelter_radius: vec![0.0],
+            seeds: vec![1, 2, 3],
+        };
+        for config in sweep.configs() {
+            let runs: Vec<_> = sweep.seeds.iter().map(|&seed| sweep.run_one(config, seed)).collect();
+            let summary = sweep.run(config);
+            assert_eq!(summary.runs, 3);
+
+            let mean = runs.iter().map(|r| r.mean_population).sum::<f32>() / 3.0;
+            assert!((summary.mean_population - mean).abs() < 1e-4, "{} vs {mean}", summary.mean_population);
+            assert!(runs.iter().all(|r| r.mean_population <= sweep.boids as f32));
+
+            let extinct: Vec<_> = runs.iter().filter_map(|r| r.extinction_tick).collect();
+            assert_eq!(summary.extinctions as usize, extinct.len());
+            let mean_tick = (!extinct.is_empty()).then(|| extinct.iter().sum::<u32>() as f32 / extinct.len() as f32);
+            assert_eq!(summary.mean_extinction_tick, mean_tick);
+            assert_eq!(summary.to_csv().split(',').count(), Summary::CSV_HEADER.split(',').count());
+        }
+    }
+}
@@ -0,0 +1,176 @@
+//! ANSI renderer for terminals.
+//!
+//! The world is scaled onto a grid of half-block cells: each character cell
+//! holds two vertically stacked pixels, drawn as `▀` with the top pixel as the
+//! foreground colour and the bottom pixel as the background.
+
+use std::io::{self, Write};
+
+use crate::color::hsl;
+use crate::vector::Vec2;
+use crate::world::World;
+
+const BACKGROUND: [u8; 3] = [17, 24, 39];
+
+pub struct TermCanvas {
+    /// Size in character cells
+    pub cols: u16,
+    pub rows: u16,
+    /// `cols × rows*2` pixels, row-major
+    pixels: Vec<[u8; 3]>,
+    /// World units → pixels
+    scale_x: f32,
+    scale_y: f32,
+}
+
+impl TermCanvas {
+    pub fn new(cols: u16, rows: u16) -> Self {
+        let (cols, rows) = (cols.max(1), rows.max(1));
+        Self {
+            cols,
+            rows,
+            pixels: vec![BACKGROUND; cols as usize * rows as usize * 2],
+            scale_x: 1.0,
+            scale_y: 1.0,
+        }
+    }
+
+    fn pixel_height(&self) -> i32 {
+        self.rows as i32 * 2
+    }
+
+    #[inline]
+    fn set(&mut self, x: i32, y: i32, rgb: [u8; 3]) {
+        if x < 0 || y < 0 || x >= self.cols as i32 || y >= self.pixel_height() { return; }
+        self.pixels[y as usize * self.cols as usize + x as usize] = rgb;
+    }
+
+    /// Plot a world-space point
+    fn point(&mut self, x: f32, y: f32, rgb: [u8; 3]) {
+        self.set((x * self.scale_x) as i32, (y * self.scale_y) as i32, rgb);
+    }
+
+    /// World-space circle; `outline` draws only the rim
+    fn circle(&mut self, cx: f32, cy: f32, r: f32, rgb: [u8; 3], outline: bool) {
+        let (px, py) = (cx * self.scale_x, cy * self.scale_y);
+        let (rx, ry) = ((r * self.scale_x).max(0.5), (r * self.scale_y).max(0.5));
+        for y in (py - ry).floor() as i32..=(py + ry).ceil() as i32 {
+            for x in (px - rx).floor() as i32..=(px + rx).ceil() as i32 {
+                let dx = (x as f32 + 0.5 - px) / rx;
+                let dy = (y as f32 + 0.5 - py) / ry;
+                let d = dx * dx + dy * dy;
+                // Rim thickness of about one pixel at this radius
+                let rim = 1.0 - 2.0 / rx.min(ry).max(1.0);
+                if d <= 1.0 && (!outline || d >= rim) {
+                    self.set(x, y, rgb);
+                }
+            }
+        }
+    }
+
+    /// World-space line, plotted densely enough to leave no gaps
+    fn line(&mut self, a: Vec2, b: Vec2, rgb: [u8; 3]) {
+        let (dx, dy) = ((b.x - a.x) * self.scale_x, (b.y - a.y) * self.scale_y);
+        let steps = dx.abs().max(dy.abs()).ceil().max(1.0) as i32;
+        for i in 0..=steps {
+            let t = i as f32 / steps as f32;
+            self.point(a.x + (b.x - a.x) * t, a.y + (b.y - a.y) * t, rgb);
+        }
+    }
+
+    /// Draw the current state of `world`, scaled to fill the canvas
+    pub fn draw_world(&mut self, world: &World) {
+        self.scale_x = self.cols as f32 / world.width.max(1.0);
+        self.scale_y = self.pixel_height() as f32 / world.height.max(1.0);
+        self.pixels.fill(BACKGROUND);
+
+        for &(x, y, r) in &world.shelters {
+            self.circle(x, y, r, [160, 120, 80], true);
+        }
+        for &(x, y, amount) in &world.food_sources {
+            self.circle(x, y, 4.0 + amount.max(0.0) / 100.0 * 8.0, [74, 180, 90], false);
+        }
+        for o in world.obstacles.as_slice() {
+            self.circle(o.position.x, o.position.y, o.radius, [110, 100, 90], false);
+        }
+        for poly in world.obstacles.polygons() {
+            for edge in poly.edges() {
+                self.line(edge.a, edge.b, [110, 100, 90]);
+            }
+        }
+        for w in world.obstacles.walls() {
+            self.line(w.a, w.b, [150, 135, 120]);
+        }
+        for bug in &world.bugs {
+            let rgb = if bug.brightness > 0.3 { [240, 255, 140] } else { hsl(bug.hue, 0.8, 0.6) };
+            self.point(bug.position.x, bug.position.y, rgb);
+        }
+        for b in &world.boids {
+            self.point(b.position.x, b.position.y, hsl(b.hue, 0.7, 0.6));
+        }
+        for p in &world.predators {
+            self.point(p.position.x, p.position.y, [240, 60, 60]);
+        }
+    }
+
+    /// Write the canvas as ANSI truecolor text, starting at the top-left corner
+    pub fn write_ansi<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        let cols = self.cols as usize;
+        let mut last: Option<([u8; 3], [u8; 3])> = None;
+        write!(out, "\x1b[H")?;
+        for row in 0..self.rows as usize {
+            let top = &self.pixels[row * 2 * cols..(row * 2 + 1) * cols];
+            let bottom = &self.pixels[(row * 2 + 1) * cols..(row * 2 + 2) * cols];
+            for (&fg, &bg) in top.iter().zip(bottom) {
+                // Only emit colour codes when they change
+                if last != Some((fg, bg)) {
+                    write!(out, "\x1b[38;2;{};{};{};48;2;{};{};{}m", fg[0], fg[1], fg[2], bg[0], bg[1], bg[2])?;
+                    last = Some((fg, bg));
+                }
+                out.write_all("▀".as_bytes())?;
+            }
+            write!(out, "\x1b[0m")?;
+            last = None;
+            if row + 1 < self.rows as usize {
+                write!(out, "\r\n")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn world_lands_on_the_right_half_blocks() {
+        let _rng = crate::rng_lock();
+        let mut world = World::new(200.0, 160.0, 0);
+        world.obstacles.clear();
+        world.shelters.clear();
+        world.add_boid(105.0, 85.0, 0);
+        world.add_predator(15.0, 155.0);
+
+        let mut canvas = TermCanvas::new(20, 8);
+        canvas.draw_world(&world);
+        // 10 world units to a pixel each way
+        let boid = hsl(world.boids[0].hue, 0.7, 0.6);
+        assert_eq!(canvas.pixels[8 * 20 + 10], boid);
+        assert_eq!(canvas.pixels[15 * 20 + 1], [240, 60, 60]);
+        assert_eq!(canvas.pixels.iter().filter(|&&p| p != BACKGROUND).count(), 2);
+
+        let mut out = Vec::new();
+        canvas.write_ansi(&mut out).unwrap();
+        let text = String::from_utf8(out).unwrap();
+        let lines: Vec<_> = text.strip_prefix("\x1b[H").unwrap().split("\r\n").collect();
+        assert_eq!(lines.len(), 8);
+        for line in &lines {
+            assert_eq!(line.matches('▀').count(), 20);
+            assert!(line.ends_with("\x1b[0m"));
+        }
+        // The boid is the top half of row 4's 11th cell
+        let [r, g, b] = boid;
+        assert!(lines[4].contains(&format!("\x1b[38;2;{r};{g};{b};48;2;17;24;39m▀")));
+    }
+}
@@ -0,0 +1,349 @@
+//! A coarse raster of ground types laid over the world.
+//!
+//! Each cell is a `Zone`, which decides what spawns there, how fast predators
+//! can chase through it and whether boids get their breath back over it. The
+//! map is stretched to cover the whole world, so a 30x15 map works at any
+//! size. Maps load from an array of zone indices, from text (one character
+//! per cell) or from RGBA pixels matched against `Zone::rgb`.
+
+use crate::vector::Vec2;
+
+/// Cell size of the default map
+const DEFAULT_CELL: f32 = 30.0;
+/// Depth of the wooded strip along the bottom of the default map
+pub(crate) const GROUND_DEPTH: f32 = 120.0;
+
+/// 0 = meadow, 1 = forest, 2 = water, 3 = rock, 4 = safe (the order
+/// `get_terrain_data` reports)
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Zone {
+    /// Open air over grass: where flocks start out
+    #[default]
+    Meadow,
+    /// Trees: rich in bugs, slow going for predators, shade to rest in
+    Forest,
+    Water,
+    /// Bare rock: nothing grows, and predators ride the updrafts
+    Rock,
+    /// Dense cover predators struggle into, where boids recover fastest
+    Safe,
+}
+
+/// What is being placed by `TerrainMap::random_point`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Spawn {
+    /// Boids and predators
+    Flyer,
+    Bug,
+    Food,
+}
+
+impl Zone {
+    pub const ALL: [Zone; 5] = [Zone::Meadow, Zone::Forest, Zone::Water, Zone::Rock, Zone::Safe];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Zone::Meadow => "meadow",
+            Zone::Forest => "forest",
+            Zone::Water => "water",
+            Zone::Rock => "rock",
+            Zone::Safe => "safe",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|z| z.name() == name)
+    }
+
+    pub fn from_index(index: u8) -> Option<Self> {
+        Self::ALL.get(index as usize).copied()
+    }
+
+    /// Map text: `.` meadow, `f` forest, `~` water, `#` rock, `s` safe, or
+    /// the zone's index as a digit
+    pub fn from_char(c: char) -> Option<Self> {
+        match c {
+            '.' => Some(Zone::Meadow),
+            'f' | 'F' => Some(Zone::Forest),
+            '~' => Some(Zone::Water),
+            '#' => Some(Zone::Rock),
+            's' | 'S' => Some(Zone::Safe),
+            _ => Self::from_index(c.to_digit(10)? as u8),
+        }
+    }
+
+    /// Colour the zone is drawn with, and matched against when loading an image
+    pub fn rgb(self) -> [u8; 3] {
+        match self {
+            Zone::Meadow => [90, 140, 60],
+            Zone::Forest => [30, 80, 40],
+            Zone::Water => [40, 90, 160],
+            Zone::Rock => [120, 110, 100],
+            Zone::Safe => [200, 170, 90],
+        }
+    }
+
+    /// The zone whose colour is nearest `rgb`
+    pub fn from_rgb(rgb: [u8; 3]) -> Self {
+        let dist = |z: &Zone| {
+            z.rgb().iter().zip(&rgb).map(|(&a, &b)| (a as i32 - b as i32).pow(2)).sum::<i32>()
+        };
+        Self::ALL.into_iter().min_by_key(dist).unwrap_or_default()
+    }
+
+    /// Relative chance of something spawning in a cell of this zone. Food
+    /// grows in the open or in cover, never under trees.
+    pub fn spawn_weight(self, spawn: Spawn) -> f32 {
+        match (self, spawn) {
+            (Zone::Meadow, _) => 1.0,
+            (Zone::Forest, Spawn::Bug) => 1.5,
+            (Zone::Water, Spawn::Bug) => 0.5,
+            (Zone::Safe, Spawn::Bug) => 0.5,
+            (Zone::Safe, Spawn::Food) => 1.0,
+            _ => 0.0,
+        }
+    }
+
+    /// Multiplier on predators' top speed
+    pub fn predator_speed(self) -> f32 {
+        match self {
+            Zone::Meadow | Zone::Water => 1.0,
+            Zone::Forest => 0.7,
+            Zone::Rock => 1.2,
+            Zone::Safe => 0.5,
+        }
+    }
+
+    /// Fatigue boids shed each tick over this zone, on top of any resting
+    pub fn fatigue_recovery(self) -> f32 {
+        match self {
+            Zone::Forest => 0.02,
+            Zone::Safe => 0.03,
+            _ => 0.0,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct TerrainMap {
+    cols: usize,
+    rows: usize,
+    /// Size of a cell in world pixels
+    cell_w: f32,
+    cell_h: f32,
+    /// Row-major, `cols * rows` long
+    zones: Vec<Zone>,
+}
+
+impl TerrainMap {
+    /// A `cols` by `rows` map stretched over a `width` by `height` world.
+    /// Returns None if `zones` is not `cols * rows` long or the map is empty.
+    pub fn new(cols: usize, rows: usize, zones: Vec<Zone>, width: f32, height: f32) -> Option<Self> {
+        if cols == 0 || rows == 0 || zones.len() != cols * rows {
+            return None;
+        }
+        Some(Self {
+            cols,
+            rows,
+            cell_w: width / cols as f32,
+            cell_h: height / rows as f32,
+            zones,
+        })
+    }
+
+    /// Meadow everywhere but a wooded strip along the bottom with a pond in
+    /// the middle, matching the scenery the browser draws
+    pub fn standard(width: f32, height: f32) -> Self {
+        let cols = (width / DEFAULT_CELL).ceil().max(1.0) as usize;
+        let rows = (height / DEFAULT_CELL).ceil().max(1.0) as usize;
+        let (cell_w, cell_h) = (width / cols as f32, height / rows as f32);
+        let pond = Vec2::new(width * 0.5, height - 80.0);
+        let mut zones = Vec::with_capacity(cols * rows);
+        for row in 0..rows {
+            for col in 0..cols {
+                let x = (col as f32 + 0.5) * cell_w;
+                let y = (row as f32 + 0.5) * cell_h;
+                let (dx, dy) = ((x - pond.x) / 100.0, (y - pond.y) / 50.0);
+                zones.push(if y < height - GROUND_DEPTH {
+                    Zone::Meadow
+                } else if dx * dx + dy * dy < 1.0 {
+                    Zone::Water
+                } else {
+                    Zone::Forest
+                });
+            }
+        }
+        Self { cols, rows, cell_w, cell_h, zones }
+    }
+
+    /// Zone indices (see `Zone`), row by row. Unknown indices are meadow.
+    pub fn from_indices(cols: usize, rows: usize, indices: &[u8], width: f32, height: f32) -> Option<Self> {
+        let zones = indices.iter().map(|&i| Zone::from_index(i).unwrap_or_default()).collect();
+        Self::new(cols, rows, zones, width, height)
+    }
+
+    /// RGBA8 pixels, one per cell, each taking the zone of the nearest colour
+    pub fn from_rgba(cols: usize, rows: usize, pixels: &[u8], width: f32, height: f32) -> Option<Self> {
+        let zones = pixels.chunks_exact(4).map(|p| Zone::from_rgb([p[0], p[1], p[2]])).collect();
+        Self::new(cols, rows, zones, width, height)
+    }
+
+    /// Text with one line per row and one character per cell (see
+    /// `Zone::from_char`). Blank lines are skipped; errors name the line.
+    pub fn parse(text: &str, width: f32, height: f32) -> Result<Self, String> {
+        let mut cols = 0;
+        let mut zones = Vec::new();
+        for (n, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() { continue; }
+            let row = line.chars()
+                .map(|c| Zone::from_char(c).ok_or_else(|| format!("line {}: unknown zone '{}'", n + 1, c)))
+                .collect::<Result<Vec<_>, _>>()?;
+            if cols == 0 {
+                cols = row.len();
+            } else if row.len() != cols {
+                return Err(format!("line {}: {} cells, expected {}", n + 1, row.len(), cols));
+            }
+            zones.extend(row);
+        }
+        let rows = zones.len().checked_div(cols).unwrap_or(0);
+        Self::new(cols, rows, zones, width, height).ok_or_else(|| "empty terrain map".to_string())
+    }
+
+    /// The same map stretched over a `width` by `height` world instead
+    pub fn stretched(&self, width: f32, height: f32) -> Self {
+        Self {
+            cell_w: width / self.cols as f32,
+            cell_h: height / self.rows as f32,
+            ..self.clone()
+        }
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn zones(&self) -> &[Zone] {
+        &self.zones
+    }
+
+    /// Width and height of a cell in world pixels
+    pub fn cell_size(&self) -> (f32, f32) {
+        (self.cell_w, self.cell_h)
+    }
+
+    /// Zone under a world point; points off the map take the nearest edge cell
+    pub fn zone_at(&self, x: f32, y: f32) -> Zone {
+        let col = ((x / self.cell_w).max(0.0) as usize).min(self.cols - 1);
+        let row = ((y / self.cell_h).max(0.0) as usize).min(self.rows - 1);
+        self.zones[row * self.cols + col]
+    }
+
+    /// Change one cell. Out-of-range cells are ignored.
+    pub fn set_zone(&mut self, col: usize, row: usize, zone: Zone) {
+        if col < self.cols && row < self.rows {
+            self.zones[row * self.cols + col] = zone;
+        }
+    }
+
+    /// A random point for `spawn`, choosing cells by their zone's weight and
+    /// a spot within the cell uniformly. `rand` yields 0..1. None if no cell
+    /// allows it.
+    pub fn random_point(&self, spawn: Spawn, mut rand: impl FnMut() -> f32) -> Option<Vec2> {
+        let total: f32 = self.zones.iter().map(|z| z.spawn_weight(spawn)).sum();
+        if total <= 0.0 {
+            return None;
+        }
+        let mut roll = rand() * total;
+        // Falls back to the last allowed cell if rounding leaves some roll over
+        let mut cell = 0;
+        for (i, z) in self.zones.iter().enumerate() {
+            let w = z.spawn_weight(spawn);
+            if w <= 0.0 { continue; }
+            cell = i;
+            if roll < w { break; }
+            roll -= w;
+        }
+        let (col, row) = (cell % self.cols, cell / self.cols);
+        Some(Vec2::new(
+            (col as f32 + rand()) * self.cell_w,
+            (row as f32 + rand()) * self.cell_h,
+        ))
+    }
+
+    /// Flat `[cols, rows, zone, zone, ...]` with zones row by row as indices
+    pub fn render_data(&self) -> Vec<f32> {
+        let mut data = Vec::with_capacity(2 + self.zones.len());
+        data.push(self.cols as f32);
+        data.push(self.rows as f32);
+        data.extend(self.zones.iter().map(|&z| z as u8 as f32));
+        data
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_names_the_bad_line() {
+        let map = TerrainMap::parse("\n.f~\n#s0\n", 300.0, 200.0).unwrap();
+        assert_eq!((map.cols(), map.rows()), (3, 2));
+        assert_eq!(map.zones()[3..], [Zone::Rock, Zone::Safe, Zone::Meadow]);
+
+        assert_eq!(TerrainMap::parse("..\n.x", 1.0, 1.0).unwrap_err(), "line 2: unknown zone 'x'");
+        assert_eq!(TerrainMap::parse("..\n...", 1.0, 1.0).unwrap_err(), "line 2: 3 cells, expected 2");
+        assert_eq!(TerrainMap::parse("..\n9", 1.0, 1.0).unwrap_err(), "line 2: unknown zone '9'");
+        assert_eq!(TerrainMap::parse(" \n\n", 1.0, 1.0).unwrap_err(), "empty terrain map");
+    }
+
+    #[test]
+    fn rgba_pixels_take_the_nearest_colour() {
+        let pixels = [
+            95, 135, 65, 255, // meadow, slightly off
+            0, 60, 20, 0,     // dark green: forest
+            0, 0, 255, 255,   // pure blue: water
+            255, 220, 120, 9, // pale yellow: safe
+        ];
+        let map = TerrainMap::from_rgba(2, 2, &pixels, 100.0, 100.0).unwrap();
+        assert_eq!(map.zones(), [Zone::Meadow, Zone::Forest, Zone::Water, Zone::Safe]);
+        for z in Zone::ALL {
+            assert_eq!(Zone::from_rgb(z.rgb()), z);
+        }
+        assert!(TerrainMap::from_rgba(2, 2, &pixels[..12], 100.0, 100.0).is_none());
+    }
+
+    #[test]
+    fn points_off_the_map_take_the_nearest_edge_cell() {
+        let map = TerrainMap::parse(".f\n~#", 200.0, 100.0).unwrap();
+        assert_eq!(map.zone_at(50.0, 25.0), Zone::Meadow);
+        assert_eq!(map.zone_at(150.0, 75.0), Zone::Rock);
+        assert_eq!(map.zone_at(-40.0, -40.0), Zone::Meadow);
+        assert_eq!(map.zone_at(500.0, -1.0), Zone::Forest);
+        assert_eq!(map.zone_at(-1.0, 500.0), Zone::Water);
+        assert_eq!(map.zone_at(200.0, 100.0), Zone::Rock);
+    }
+
+    #[test]
+    fn random_points_follow_zone_weights() {
+        // Bugs: meadow 1, forest 1.5, rock never
+        let map = TerrainMap::parse(".f#", 300.0, 100.0).unwrap();
+        let mut counts = [0; 3];
+        let steps = 1000;
+        for i in 0..steps {
+            let mut rolls = [(i as f32 + 0.5) / steps as f32, 0.25, 0.75].into_iter();
+            let p = map.random_point(Spawn::Bug, || rolls.next().unwrap()).unwrap();
+            assert!((p.y - 75.0).abs() < 1e-3);
+            counts[(p.x / 100.0) as usize] += 1;
+        }
+        assert_eq!(counts, [400, 600, 0]);
+
+        let rock = TerrainMap::parse("##", 100.0, 100.0).unwrap();
+        assert!(rock.random_point(Spawn::Food, || 0.5).is_none());
+        assert!(map.random_point(Spawn::Food, || 0.999).is_some_and(|p| p.x < 100.0));
+    }
+}
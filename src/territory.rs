@@ -0,0 +1,278 @@
+//! Territory: a coarse grid of cells that boid species and predator packs
+//! claim by spending time in them.
+//!
+//! Each cell has at most one owner and a claim strength 0..1. Presence by the
+//! owner strengthens the claim, presence by anyone else wears it down until
+//! the cell changes hands, and every claim fades slowly so abandoned ground
+//! lapses.
+
+use crate::vector::Vec2;
+
+/// Grid cell size in pixels
+pub const CELL_SIZE: f32 = 50.0;
+/// Claim strength one animal adds, or knocks off a rival's claim, per tick
+const CLAIM_RATE: f32 = 0.001;
+/// Strength every claim loses per tick; a full claim lapses in 2000 ticks
+const DECAY: f32 = 0.0005;
+/// How many rings of cells `pressure` searches for a way out of foreign ground
+const ESCAPE_RINGS: i32 = 4;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Owner {
+    /// Boids of one species; hybrids claim nothing
+    Species(u8),
+    /// A predator pack, by `pack_id`
+    Pack(u32),
+}
+
+impl Owner {
+    /// Code used in render data: species 0 and 1 are 0 and 1, pack `n` is
+    /// `2 + n`
+    pub fn code(self) -> f32 {
+        match self {
+            Owner::Species(s) => s as f32,
+            Owner::Pack(p) => 2.0 + p as f32,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Default)]
+struct Claim {
+    owner: Option<Owner>,
+    strength: f32,
+}
+
+pub struct TerritoryGrid {
+    cols: usize,
+    rows: usize,
+    cells: Vec<Claim>,
+}
+
+impl TerritoryGrid {
+    /// Unclaimed ground covering a `width` by `height` world
+    pub fn new(width: f32, height: f32) -> Self {
+        let cols = (width / CELL_SIZE).ceil().max(1.0) as usize;
+        let rows = (height / CELL_SIZE).ceil().max(1.0) as usize;
+        Self { cols, rows, cells: vec![Claim::default(); cols * rows] }
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// Column and row under a world point, wrapping like the world does
+    #[inline]
+    fn cell(&self, x: f32, y: f32) -> (usize, usize) {
+        let col = ((x / CELL_SIZE).floor() as i64).rem_euclid(self.cols as i64) as usize;
+        let row = ((y / CELL_SIZE).floor() as i64).rem_euclid(self.rows as i64) as usize;
+        (col, row)
+    }
+
+    #[inline]
+    fn claim(&self, col: usize, row: usize) -> Claim {
+        self.cells[row * self.cols + col]
+    }
+
+    /// Owner and claim strength of the cell under a point
+    pub fn claim_at(&self, x: f32, y: f32) -> Option<(Owner, f32)> {
+        let (col, row) = self.cell(x, y);
+        let c = self.claim(col, row);
+        c.owner.map(|o| (o, c.strength))
+    }
+
+    pub fn owner_at(&self, x: f32, y: f32) -> Option<Owner> {
+        self.claim_at(x, y).map(|(o, _)| o)
+    }
+
+    /// One tick of `owner` being present at a point
+    pub fn mark(&mut self, owner: Owner, x: f32, y: f32) {
+        let (col, row) = self.cell(x, y);
+        let c = &mut self.cells[row * self.cols + col];
+        if c.owner == Some(owner) {
+            c.strength = (c.strength + CLAIM_RATE).min(1.0);
+        } else if c.strength > CLAIM_RATE {
+            c.strength -= CLAIM_RATE;
+        } else {
+            *c = Claim { owner: Some(owner), strength: CLAIM_RATE };
+        }
+    }
+
+    /// Fade every claim by one tick
+    pub fn update(&mut self) {
+        for c in &mut self.cells {
+            c.strength -= DECAY;
+            if c.strength <= 0.0 {
+                *c = Claim::default();
+            }
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.cells.fill(Claim::default());
+    }
+
+    /// Which way, and how strongly, ground held by owners `is_foreign` picks
+    /// out lies from a point: the claim strengths of the surrounding cells
+    /// weighted by their direction. On foreign ground the cell underfoot adds
+    /// its strength pointing away from the nearest cell that is not foreign,
+    /// or toward the cell's centre if none is close. Zero on free ground away
+    /// from borders.
+    pub fn pressure(&self, x: f32, y: f32, is_foreign: impl Fn(Owner) -> bool) -> Vec2 {
+        let (col, row) = self.cell(x, y);
+        let here = self.claim(col, row);
+        let mut p = if here.owner.is_some_and(&is_foreign) {
+            let out = self.escape(col, row, &is_foreign).unwrap_or_else(|| {
+                let centre = Vec2::new((col as f32 + 0.5) * CELL_SIZE, (row as f32 + 0.5) * CELL_SIZE);
+                Vec2::new(x, y).sub(centre).normalize()
+            });
+            out.mult(-here.strength)
+        } else {
+            Vec2::zero()
+        };
+        for dr in -1i32..=1 {
+            for dc in -1i32..=1 {
+                if dr == 0 && dc == 0 { continue; }
+                let c = (col as i32 + dc).rem_euclid(self.cols as i32) as usize;
+                let r = (row as i32 + dr).rem_euclid(self.rows as i32) as usize;
+                let claim = self.claim(c, r);
+                if claim.owner.is_some_and(&is_foreign) {
+                    p.x += dc as f32 * claim.strength;
+                    p.y += dr as f32 * claim.strength;
+                }
+            }
+        }
+        p
+    }
+
+    /// Unit direction from a cell to the nearest cell within `ESCAPE_RINGS`
+    /// that is not held by a foreign owner
+    fn escape(&self, col: usize, row: usize, is_foreign: impl Fn(Owner) -> bool) -> Option<Vec2> {
+        for ring in 1..=ESCAPE_RINGS {
+            let mut best: Option<(i32, i32)> = None;
+            for dr in -ring..=ring {
+                for dc in -ring..=ring {
+                    if dr.abs() != ring && dc.abs() != ring { continue; }
+                    let c = (col as i32 + dc).rem_euclid(self.cols as i32) as usize;
+                    let r = (row as i32 + dr).rem_euclid(self.rows as i32) as usize;
+                    if self.claim(c, r).owner.is_some_and(&is_foreign) { continue; }
+                    if best.is_none_or(|(bc, br)| dc * dc + dr * dr < bc * bc + br * br) {
+                        best = Some((dc, dr));
+                    }
+                }
+            }
+            if let Some((dc, dr)) = best {
+                return Some(Vec2::new(dc as f32, dr as f32).normalize());
+            }
+        }
+        None
+    }
+
+    /// Share of the world `owner` holds, 0..1
+    pub fn share(&self, owner: Owner) -> f32 {
+        let held = self.cells.iter().filter(|c| c.owner == Some(owner)).count();
+        held as f32 / self.cells.len() as f32
+    }
+
+    /// Flat `[cols, rows, owner, strength, ...]` row by row, where owner is
+    /// -1 for unclaimed ground or `Owner::code`
+    pub fn render_data(&self) -> Vec<f32> {
+        let mut data = Vec::with_capacity(2 + self.cells.len() * 2);
+        data.push(self.cols as f32);
+        data.push(self.rows as f32);
+        for c in &self.cells {
+            data.push(c.owner.map_or(-1.0, Owner::code));
+            data.push(c.strength);
+        }
+        data
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Give `owner` every cell in the columns and rows at full strength
+    fn hold(grid: &mut TerritoryGrid, owner: Owner, cols: std::ops::Range<usize>, rows: std::ops::Range<usize>) {
+        for row in rows {
+            for col in cols.clone() {
+                grid.cells[row * grid.cols + col] = Claim { owner: Some(owner), strength: 1.0 };
+            }
+        }
+    }
+
+    fn centre(col: usize, row: usize) -> (f32, f32) {
+        ((col as f32 + 0.5) * CELL_SIZE, (row as f32 + 0.5) * CELL_SIZE)
+    }
+
+    #[test]
+    fn rivals_wear_a_claim_down_before_taking_it() {
+        let mut grid = TerritoryGrid::new(500.0, 500.0);
+        let (a, b) = (Owner::Species(0), Owner::Pack(3));
+        for _ in 0..10 {
+            grid.mark(a, 60.0, 60.0);
+        }
+        let (owner, strength) = grid.claim_at(60.0, 60.0).unwrap();
+        assert_eq!(owner, a);
+        assert!((strength - 10.0 * CLAIM_RATE).abs() < 1e-6);
+
+        let mut ticks = 0;
+        while grid.owner_at(60.0, 60.0) == Some(a) {
+            grid.mark(b, 60.0, 60.0);
+            ticks += 1;
+        }
+        assert!((10..=11).contains(&ticks), "{ticks}");
+        assert_eq!(grid.owner_at(60.0, 60.0), Some(b));
+        // Neighbouring cells are untouched
+        assert_eq!(grid.owner_at(110.0, 60.0), None);
+    }
+
+    #[test]
+    fn claims_lapse() {
+        let mut grid = TerritoryGrid::new(500.0, 500.0);
+        grid.mark(Owner::Species(1), 10.0, 10.0);
+        grid.update();
+        assert!(grid.owner_at(10.0, 10.0).is_some());
+        grid.update();
+        assert_eq!(grid.owner_at(10.0, 10.0), None);
+        assert_eq!(grid.share(Owner::Species(1)), 0.0);
+    }
+
+    #[test]
+    fn pressure_counts_only_foreign_owners() {
+        let mut grid = TerritoryGrid::new(500.0, 500.0);
+        hold(&mut grid, Owner::Species(1), 5..6, 0..10);
+        let (x, y) = centre(4, 4);
+        // Species 0 feels species 1's ground to the east
+        let p = grid.pressure(x, y, |o| o != Owner::Species(0));
+        assert!(p.x > 0.0 && p.y.abs() < 1e-6);
+        // Species 1 does not mind its own
+        assert_eq!(grid.pressure(x, y, |o| o != Owner::Species(1)).mag_sq(), 0.0);
+        // Nor does anyone far from the border
+        let (x, y) = centre(1, 4);
+        assert_eq!(grid.pressure(x, y, |o| o != Owner::Species(0)).mag_sq(), 0.0);
+        assert!((grid.share(Owner::Species(1)) - 0.1).abs() < 1e-6);
+    }
+
+    #[test]
+    fn deep_inside_foreign_ground_points_away_from_the_nearest_way_out() {
+        let mut grid = TerritoryGrid::new(1000.0, 500.0);
+        hold(&mut grid, Owner::Pack(0), 3..10, 0..10);
+        let foreign = |o| o != Owner::Species(0);
+        // Free ground is two cells west of column 4 and two east of column 8
+        let (x, y) = centre(4, 5);
+        let p = grid.pressure(x, y, foreign);
+        assert!(p.x > 0.0 && p.y.abs() < 1e-6, "{p:?}");
+        let (x, y) = centre(8, 5);
+        let p = grid.pressure(x, y, foreign);
+        assert!(p.x < 0.0 && p.y.abs() < 1e-6, "{p:?}");
+
+        // With no way out in reach, the way out is the nearest edge of the cell
+        hold(&mut grid, Owner::Pack(0), 0..20, 0..10);
+        let p = grid.pressure(210.0, 260.0, foreign);
+        assert!(p.x > 0.0 && p.y > 0.0, "{p:?}");
+    }
+}
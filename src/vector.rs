@@ -70,6 +70,18 @@ impl Vec2 {
         self.mag_sq().sqrt()
     }
 
+    #[inline]
+    pub fn dot(&self, other: Vec2) -> f32 {
+        self.x * other.x + self.y * other.y
+    }
+
+    /// z component of the 3D cross product; positive when `other` is
+    /// clockwise of `self` on screen (y down)
+    #[inline]
+    pub fn cross(&self, other: Vec2) -> f32 {
+        self.x * other.y - self.y * other.x
+    }
+
     #[inline]
     pub fn dist_sq(&self, other: Vec2) -> f32 {
         let dx = self.x - other.x;
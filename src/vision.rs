@@ -0,0 +1,80 @@
+//! Limited perception: a cone of view ahead of a moving animal.
+//!
+//! Anything outside the cone or beyond its range is invisible, which leaves a
+//! blind spot behind each boid and predator.
+
+use crate::vector::Vec2;
+
+/// Boids see most of the way around, with a blind spot straight behind
+pub const BOID_FOV: f32 = 300.0;
+pub const BOID_RANGE: f32 = 150.0;
+/// Predators look forward to pick out prey
+pub const PREDATOR_FOV: f32 = 120.0;
+pub const PREDATOR_RANGE: f32 = 150.0;
+/// Anything this close is noticed whatever the heading (heard or felt), so
+/// separation still keeps boids off the ones right behind them
+pub const AWARENESS_RADIUS: f32 = 30.0;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Vision {
+    /// Full width of the cone in degrees; 360 sees all round
+    fov: f32,
+    range: f32,
+    /// Cosine of half the cone width, the dot product cut-off
+    cos_half: f32,
+}
+
+impl Vision {
+    pub fn new(fov_degrees: f32, range: f32) -> Self {
+        let fov = fov_degrees.clamp(0.0, 360.0);
+        Self {
+            fov,
+            range: range.max(0.0),
+            cos_half: (fov.to_radians() * 0.5).cos(),
+        }
+    }
+
+    pub fn boid() -> Self {
+        Self::new(BOID_FOV, BOID_RANGE)
+    }
+
+    pub fn predator() -> Self {
+        Self::new(PREDATOR_FOV, PREDATOR_RANGE)
+    }
+
+    pub fn fov(&self) -> f32 {
+        self.fov
+    }
+
+    pub fn range(&self) -> f32 {
+        self.range
+    }
+
+    /// The same cone seeing only `mult` as far
+    pub fn with_range_mult(&self, mult: f32) -> Self {
+        Self { range: self.range * mult, ..*self }
+    }
+
+    /// Whether a point at `offset` from an animal moving along `velocity`
+    /// lies inside the cone, ignoring range. `dist_sq` is `offset`'s squared
+    /// length. An animal at rest has no heading and sees all round.
+    #[inline]
+    pub fn in_cone(&self, velocity: Vec2, offset: Vec2, dist_sq: f32) -> bool {
+        let dot = velocity.x * offset.x + velocity.y * offset.y;
+        self.fov >= 360.0 || dot >= self.cos_half * (dist_sq * velocity.mag_sq()).sqrt()
+    }
+
+    /// Whether a point at `offset` is in range and either inside the cone or
+    /// within `AWARENESS_RADIUS`
+    #[inline]
+    pub fn sees(&self, velocity: Vec2, offset: Vec2, dist_sq: f32) -> bool {
+        dist_sq <= self.range * self.range
+            && (dist_sq < AWARENESS_RADIUS * AWARENESS_RADIUS || self.in_cone(velocity, offset, dist_sq))
+    }
+}
+
+impl Default for Vision {
+    fn default() -> Self {
+        Self::boid()
+    }
+}
@@ -0,0 +1,233 @@
+//! Weather: a state machine stepping between clear skies, rain, storms and
+//! snow, with odds that follow the season.
+
+use crate::season::Season;
+
+/// 0 = clear, 1 = rain, 2 = storm, 3 = snow (the order `get_stats` reports)
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Weather {
+    #[default]
+    Clear,
+    Rain,
+    Storm,
+    Snow,
+}
+
+impl Weather {
+    pub fn name(self) -> &'static str {
+        match self {
+            Weather::Clear => "clear",
+            Weather::Rain => "rain",
+            Weather::Storm => "storm",
+            Weather::Snow => "snow",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "clear" => Some(Weather::Clear),
+            "rain" => Some(Weather::Rain),
+            "storm" => Some(Weather::Storm),
+            "snow" => Some(Weather::Snow),
+            _ => None,
+        }
+    }
+
+    /// Bug spawn rate multiplier
+    pub fn bug_mult(self) -> f32 {
+        match self {
+            Weather::Clear => 1.0,
+            Weather::Rain => 0.5,
+            Weather::Storm => 0.1,
+            Weather::Snow => 0.0,
+        }
+    }
+
+    /// Multiplier on boids' fatigue gain
+    pub fn fatigue_mult(self) -> f32 {
+        match self {
+            Weather::Clear => 1.0,
+            Weather::Rain => 1.3,
+            Weather::Storm => 1.8,
+            Weather::Snow => 1.5,
+        }
+    }
+
+    /// Multiplier on boids' energy drain; cold burns the most
+    pub fn energy_mult(self) -> f32 {
+        match self {
+            Weather::Clear => 1.0,
+            Weather::Rain => 1.1,
+            Weather::Storm => 1.4,
+            Weather::Snow => 1.6,
+        }
+    }
+
+    /// Fraction of daylight that gets through the clouds
+    pub fn light(self) -> f32 {
+        match self {
+            Weather::Clear => 1.0,
+            Weather::Rain => 0.75,
+            Weather::Storm => 0.5,
+            Weather::Snow => 0.85,
+        }
+    }
+
+    /// Multiplier on predators' sight range
+    pub fn visibility(self) -> f32 {
+        match self {
+            Weather::Clear => 1.0,
+            Weather::Rain => 0.8,
+            Weather::Storm => 0.5,
+            Weather::Snow => 0.7,
+        }
+    }
+
+    /// Ticks the weather holds before it changes: (min, spread)
+    fn duration(self) -> (f32, f32) {
+        match self {
+            Weather::Clear => (1200.0, 1800.0),
+            Weather::Rain => (600.0, 1200.0),
+            Weather::Storm => (300.0, 600.0),
+            Weather::Snow => (900.0, 1500.0),
+        }
+    }
+}
+
+/// Chance of each weather (clear, rain, storm, snow) when it next changes,
+/// by season (spring, summer, autumn, winter)
+const ODDS: [[f32; 4]; 4] = [
+    [0.5, 0.4, 0.1, 0.0],
+    [0.7, 0.2, 0.1, 0.0],
+    [0.4, 0.4, 0.2, 0.0],
+    [0.4, 0.1, 0.1, 0.4],
+];
+
+pub struct WeatherSystem {
+    pub current: Weather,
+    /// Ticks until the next change
+    remaining: u32,
+    /// When false the current weather holds until changed by hand
+    pub auto: bool,
+}
+
+impl WeatherSystem {
+    pub fn new() -> Self {
+        Self { current: Weather::Clear, remaining: Self::hold(Weather::Clear), auto: true }
+    }
+
+    fn hold(weather: Weather) -> u32 {
+        let (min, spread) = weather.duration();
+        (min + rand_f32() * spread) as u32
+    }
+
+    /// Set the weather now. It lasts its usual time before changing again.
+    pub fn set(&mut self, weather: Weather) {
+        self.current = weather;
+        self.remaining = Self::hold(weather);
+    }
+
+    /// Advance one tick. `season` picks the odds for the next change.
+    pub fn update(&mut self, season: Season) {
+        if !self.auto { return; }
+        self.remaining = self.remaining.saturating_sub(1);
+        if self.remaining > 0 { return; }
+
+        let mut roll = rand_f32();
+        let mut next = Weather::Clear;
+        for (weather, &p) in [Weather::Clear, Weather::Rain, Weather::Storm, Weather::Snow].into_iter().zip(&ODDS[season.index()]) {
+            if roll < p {
+                next = weather;
+                break;
+            }
+            roll -= p;
+        }
+        self.set(next);
+    }
+}
+
+impl Default for WeatherSystem {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+static mut SEED: u32 = 24680;
+
+fn rand_f32() -> f32 {
+    unsafe {
+        SEED ^= SEED << 13;
+        SEED ^= SEED >> 17;
+        SEED ^= SEED << 5;
+        (SEED as f32) / (u32::MAX as f32)
+    }
+}
+
+pub fn set_seed(seed: u32) {
+    unsafe {
+        SEED = if seed == 0 { 24680 } else { seed };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Weather after each of the next `changes` changes
+    fn run(system: &mut WeatherSystem, season: Season, changes: usize) -> Vec<Weather> {
+        (0..changes).map(|_| {
+            while system.remaining > 1 {
+                system.update(season);
+            }
+            system.update(season);
+            system.current
+        }).collect()
+    }
+
+    #[test]
+    fn odds_cover_every_outcome() {
+        for (season, row) in Season::ALL.into_iter().zip(ODDS) {
+            assert!((row.iter().sum::<f32>() - 1.0).abs() < 1e-6, "{}", season.name());
+            assert!(row.iter().all(|&p| p >= 0.0));
+        }
+    }
+
+    #[test]
+    fn forced_weather_holds_at_least_its_minimum() {
+        let _rng = crate::rng_lock();
+        set_seed(3);
+        let mut system = WeatherSystem::new();
+        for weather in [Weather::Clear, Weather::Rain, Weather::Storm, Weather::Snow] {
+            for _ in 0..20 {
+                system.set(weather);
+                let (min, spread) = weather.duration();
+                assert!((min as u32..(min + spread) as u32).contains(&system.remaining));
+                for _ in 1..min as u32 {
+                    system.update(Season::Summer);
+                    assert_eq!(system.current, weather);
+                }
+            }
+        }
+
+        system.set(Weather::Storm);
+        system.auto = false;
+        for _ in 0..5000 {
+            system.update(Season::Spring);
+        }
+        assert_eq!(system.current, Weather::Storm);
+    }
+
+    #[test]
+    fn seeded_runs_repeat_and_follow_the_season() {
+        let _rng = crate::rng_lock();
+        set_seed(11);
+        let first = run(&mut WeatherSystem::new(), Season::Winter, 200);
+        set_seed(11);
+        assert_eq!(run(&mut WeatherSystem::new(), Season::Winter, 200), first);
+        assert!(first.contains(&Weather::Snow));
+
+        let summer = run(&mut WeatherSystem::new(), Season::Summer, 200);
+        assert!(!summer.contains(&Weather::Snow));
+        assert!(summer.contains(&Weather::Storm));
+    }
+}
@@ -1,28 +1,117 @@
 use crate::boid::{Boid, BoidState, Mutations};
-use crate::predator::Predator;
-use crate::bug::Bug;
+use crate::predator::{Predator, Prey};
+use crate::bug::{self, Bug, BugKind};
 use crate::spatial::SpatialHash;
+use crate::vector::Vec2;
+use crate::svg::{self, SvgOptions};
+use crate::clock::{FixedStep, STEP_MS};
+use crate::obstacle::Obstacles;
+use crate::vision::Vision;
+use crate::flow::FlowField;
+use crate::weather::{Weather, WeatherSystem};
+use crate::season::{Season, SeasonTable};
+use crate::terrain::{Spawn, TerrainMap};
+use crate::scent::{Channel, ScentGrid};
+use crate::territory::{Owner, TerritoryGrid};
+use std::f32::consts::TAU;
+use crate::behaviour::{Agent, Behaviour, Environment, Neighbour, NeighbourMode, Neighbourhood, Pipeline, FLEE_RADIUS, PANIC_FEAR};
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+/// Farthest a boid can sense food from: 70px scaled by the max hunger multiplier (1.5)
+const FOOD_SENSE_RADIUS: f32 = 105.0;
+/// Cell size of the boid spatial hash
+const CELL_SIZE: f32 = 50.0;
+/// Default advance of `day_time` per tick, about 4200 ticks per day
+const DAY_STEP: f32 = 0.0015;
+/// Day phase above which it counts as day (for food, dawn and dusk)
+const DAY_THRESHOLD: f32 = 0.45;
+/// Day phase below which fireflies are out; they settle at dawn
+const FIREFLY_DARK: f32 = 0.3;
+/// Chance per dark tick of a firefly appearing, before bug multipliers
+const FIREFLY_RATE: f32 = 0.1;
+const MAX_FIREFLIES: usize = 30;
+/// Food stays this far in from the world's edges, off the wrap seam
+const FOOD_MARGIN: f32 = 50.0;
+/// Spots tried for new food before giving up until a later tick
+const FOOD_TRIES: usize = 8;
+/// How far a NOCTURNAL boid notices a firefly's flash from
+const FIREFLY_LURE_RADIUS: f32 = 100.0;
 
 pub struct World {
     pub boids: Vec<Boid>,
     pub predators: Vec<Predator>,
     pub bugs: Vec<Bug>,
-    pub obstacles: Vec<(f32, f32)>,
+    pub obstacles: Obstacles,
+    /// Wind, gusts and vortices pushing everything that flies
+    pub flow: FlowField,
+    pub weather: WeatherSystem,
+    /// Ground types: where things spawn, predator speed, boid recovery
+    pub terrain: TerrainMap,
+    /// Food, danger and boid scent trails left by the flock
+    pub scent: ScentGrid,
+    /// Ground claimed by each boid species and predator pack
+    pub territory: TerritoryGrid,
     pub shelters: Vec<(f32, f32, f32)>, // x, y, radius
     pub food_sources: Vec<(f32, f32, f32)>, // x, y, amount
     pub width: f32,
     pub height: f32,
     pub time: u32,
+    /// Angle of the sun; see `day_phase`
     pub day_time: f32,
+    /// `day_time` advance per tick, before the season's day length; see
+    /// `set_day_length`
+    pub day_step: f32,
+    /// Dawns and dusks since the host last took them
+    day_events: Vec<DayEvent>,
+    /// Years since the start; see `season`
     pub season_time: f32,
+    /// Per-season bug, food, day length, breeding and predator settings
+    pub seasons: SeasonTable,
+    /// Bug spawn rate multiplier
+    pub bug_mult: f32,
+    pub clock: FixedStep,
+    pub cursor: Cursor,
+    /// Steering behaviours applied to every boid, in order
+    pub behaviours: Pipeline,
+    /// Whether alignment and cohesion use a radius or the nearest flock-mates
+    pub neighbour_mode: NeighbourMode,
+    /// View cone of each boid species (0 and 1); hybrids use their species' cone
+    pub vision: [Vision; 2],
+    /// View cone predators pick prey with
+    pub predator_vision: Vision,
     spatial_hash: SpatialHash,
+    bug_hash: SpatialHash,
+    food_hash: SpatialHash,
     next_pack_id: u32,
     // Reusable buffers
+    #[cfg(not(feature = "parallel"))]
     nearby_buffer: Vec<usize>,
+    #[cfg(not(feature = "parallel"))]
+    neighbourhood: Neighbourhood,
+    force_buffer: Vec<(f32, f32)>,
+    /// Each boid's fear of predators from the flocking pass
+    fear_buffer: Vec<f32>,
+    candidate_buffer: Vec<usize>,
     // Cached boid data for flocking (avoids borrow issues)
     boid_cache: Vec<BoidCache>,
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DayEvent {
+    Dawn,
+    Dusk,
+}
+
+/// Cursor input used by `advance`. mode: 0 = none, 1 = attract, 2 = repel
+#[derive(Clone, Copy, Default)]
+pub struct Cursor {
+    pub x: f32,
+    pub y: f32,
+    pub mode: i32,
+    pub strength: f32,
+}
+
 #[derive(Clone, Copy, Default)]
 struct BoidCache {
     x: f32,
@@ -36,11 +125,22 @@ struct BoidCache {
 
 impl World {
     pub fn new(width: f32, height: f32, start_boids: u32) -> Self {
+        Self::with_terrain(width, height, start_boids, TerrainMap::standard(width, height))
+    }
+
+    /// A world whose starting flock is placed on `terrain` rather than the
+    /// standard map
+    pub fn with_terrain(width: f32, height: f32, start_boids: u32, terrain: TerrainMap) -> Self {
         let mut world = Self {
             boids: Vec::with_capacity(start_boids as usize * 2),
             predators: Vec::new(),
             bugs: Vec::new(),
-            obstacles: Vec::new(),
+            obstacles: Obstacles::new(),
+            flow: FlowField::new(),
+            weather: WeatherSystem::new(),
+            terrain,
+            scent: ScentGrid::new(width, height),
+            territory: TerritoryGrid::new(width, height),
             shelters: vec![
                 (80.0, height - 100.0, 50.0),
                 (width - 80.0, height - 100.0, 50.0),
@@ -50,10 +150,28 @@ impl World {
             height,
             time: 0,
             day_time: 0.0,
+            day_step: DAY_STEP,
+            day_events: Vec::new(),
             season_time: 0.0,
-            spatial_hash: SpatialHash::new(50.0),
+            seasons: SeasonTable::default(),
+            bug_mult: 1.0,
+            clock: FixedStep::new(),
+            cursor: Cursor::default(),
+            behaviours: Pipeline::standard(),
+            neighbour_mode: NeighbourMode::Metric,
+            vision: [Vision::boid(); 2],
+            predator_vision: Vision::predator(),
+            spatial_hash: SpatialHash::new(CELL_SIZE),
+            bug_hash: SpatialHash::new(50.0),
+            food_hash: SpatialHash::new(FOOD_SENSE_RADIUS),
             next_pack_id: 0,
+            #[cfg(not(feature = "parallel"))]
             nearby_buffer: Vec::with_capacity(100),
+            #[cfg(not(feature = "parallel"))]
+            neighbourhood: Neighbourhood::default(),
+            force_buffer: Vec::with_capacity(start_boids as usize * 2),
+            fear_buffer: Vec::with_capacity(start_boids as usize * 2),
+            candidate_buffer: Vec::with_capacity(32),
             boid_cache: Vec::with_capacity(start_boids as usize * 2),
         };
 
@@ -61,103 +179,123 @@ impl World {
         let num_s0 = (start_boids as f32 * 0.55) as u32;
         let num_s1 = start_boids - num_s0;
 
-        for _ in 0..num_s0 {
-            let x = rand_f32() * width;
-            let y = rand_f32() * (height - 120.0);
-            world.boids.push(Boid::new(x, y, 0, false));
-        }
-
-        for _ in 0..num_s1 {
-            let x = rand_f32() * width;
-            let y = rand_f32() * (height - 120.0);
-            world.boids.push(Boid::new(x, y, 1, false));
+        for i in 0..num_s0 + num_s1 {
+            let p = world.spawn_point(Spawn::Flyer);
+            world.boids.push(Boid::new(p.x, p.y, (i >= num_s0) as u8, false));
         }
 
         world
     }
 
+    /// Feed `dt_ms` of real time through the fixed-step clock, running a whole
+    /// number of ticks with the stored cursor. Returns the interpolation alpha.
+    pub fn advance(&mut self, dt_ms: f32) -> f32 {
+        let steps = self.clock.consume(dt_ms);
+        let c = self.cursor;
+        for _ in 0..steps {
+            self.tick(c.x, c.y, c.mode, c.strength);
+        }
+        self.clock.alpha()
+    }
+
     pub fn tick(&mut self, cursor_x: f32, cursor_y: f32, cursor_mode: i32, cursor_strength: f32) {
-        self.time += 16;
-        self.day_time += 0.0015;
-        let day_phase = (self.day_time.sin() + 1.0) / 2.0;
+        self.time += STEP_MS as u32;
         self.season_time += 0.0002;
-
-        // Rebuild spatial hash and cache boid data
-        self.spatial_hash.clear();
-        self.boid_cache.clear();
-        for (i, boid) in self.boids.iter().enumerate() {
-            self.spatial_hash.insert(i, boid.position.x, boid.position.y);
-            self.boid_cache.push(BoidCache {
-                x: boid.position.x,
-                y: boid.position.y,
-                vx: boid.velocity.x,
-                vy: boid.velocity.y,
-                species: boid.species,
-                is_hybrid: boid.is_hybrid,
-                state: match boid.state {
-                    BoidState::Normal => 0,
-                    BoidState::Perching => 1,
-                    BoidState::Collapsed => 2,
-                    BoidState::Fishing => 3,
-                },
-            });
+        let season = self.season();
+        let params = *self.seasons.get(season);
+        let was_day = self.day_phase() > DAY_THRESHOLD;
+        self.day_time += self.day_step / params.day_length;
+        let day_phase = self.day_phase();
+        let is_day = day_phase > DAY_THRESHOLD;
+        if is_day != was_day {
+            self.day_events.push(if is_day { DayEvent::Dawn } else { DayEvent::Dusk });
         }
+        self.flow.set_season_time(self.season_time);
+        self.scent.update();
+        self.territory.update();
+        self.weather.update(season);
+        let weather = self.weather.current;
+        let light = self.light_level();
 
-        // Spawn bugs occasionally
-        let season_index = ((self.season_time % 1.0) * 4.0) as u32;
-        let bug_rate = match season_index {
-            1 => 0.15,
-            3 => 0.03,
-            _ => 0.08,
-        };
-        let max_bugs = match season_index {
-            1 => 60,
-            3 => 20,
-            _ => 40,
-        };
+        self.cache_boids();
 
-        if rand_f32() < bug_rate && self.bugs.len() < max_bugs {
-            let x = rand_f32() * self.width;
-            let y = rand_f32() * (self.height - 100.0);
-            self.bugs.push(Bug::new(x, y));
+        // Spawn bugs occasionally, and fireflies after dark. The season's
+        // bug cap counts common bugs only. Fireflies are gone by day.
+        let dark = day_phase < FIREFLY_DARK;
+        if !dark {
+            self.bugs.retain(|b| b.kind != BugKind::Firefly);
+        }
+        let fireflies = self.bugs.iter().filter(|b| b.kind == BugKind::Firefly).count();
+        let bug_chance = params.bug_rate * self.bug_mult * weather.bug_mult();
+        if rand_f32() < bug_chance && self.bugs.len() - fireflies < params.max_bugs {
+            if let Some(p) = self.terrain.random_point(Spawn::Bug, rand_f32) {
+                self.bugs.push(Bug::new(p.x, p.y));
+            }
+        }
+        let firefly_chance = FIREFLY_RATE * self.bug_mult * weather.bug_mult();
+        if dark && rand_f32() < firefly_chance && fireflies < MAX_FIREFLIES {
+            if let Some(p) = self.terrain.random_point(Spawn::Bug, rand_f32) {
+                self.bugs.push(Bug::firefly(p.x, p.y));
+            }
         }
 
         // Update bugs
-        self.bugs.retain_mut(|bug| bug.update(self.width, self.height, &self.obstacles));
+        self.bugs.retain_mut(|bug| bug.update(self.width, self.height, &self.obstacles, &self.flow));
+        bug::sync_fireflies(&mut self.bugs);
 
-        // Spawn food occasionally
-        let food_chance = match season_index {
-            1 => 0.003,
-            3 => 0.0003,
-            _ => 0.0015,
-        };
-        let is_day = day_phase > 0.45;
-        if is_day && rand_f32() < food_chance && self.food_sources.len() < 5 {
-            let x = 50.0 + rand_f32() * (self.width - 100.0);
-            let y = 50.0 + rand_f32() * (self.height - 180.0);
-            self.food_sources.push((x, y, 100.0));
+        // Spawn food occasionally. On the standard map this is the meadow
+        // clear of the edges, the band food has always grown in.
+        if is_day && rand_f32() < params.food_chance && self.food_sources.len() < 5 {
+            let (w, h) = (self.width, self.height);
+            let spot = (0..FOOD_TRIES)
+                .filter_map(|_| self.terrain.random_point(Spawn::Food, rand_f32))
+                .find(|p| {
+                    (FOOD_MARGIN..=w - FOOD_MARGIN).contains(&p.x)
+                        && (FOOD_MARGIN..=h - FOOD_MARGIN).contains(&p.y)
+                });
+            if let Some(p) = spot {
+                self.food_sources.push((p.x, p.y, 100.0));
+            }
         }
 
         // Update food sources
         self.food_sources.retain(|&(_, _, amount)| amount > 0.0);
 
-        // Prepare data for predator hunting
+        // Prepare data for predator hunting. Shelter cover is resolved once per
+        // boid here rather than by every predator.
         let boid_data: Vec<_> = self.boids.iter().map(|b| {
-            (
-                b.position.x,
-                b.position.y,
-                b.velocity.x,
-                b.velocity.y,
-                b.energy,
-                b.state == BoidState::Collapsed,
-                b.mutations.has(Mutations::TASTY),
-            )
+            let in_shelter = self.shelters.iter().any(|&(sx, sy, sr)| {
+                let dx = sx - b.position.x;
+                let dy = sy - b.position.y;
+                dx * dx + dy * dy < sr * sr
+            });
+            Prey {
+                x: b.position.x,
+                y: b.position.y,
+                vx: b.velocity.x,
+                vy: b.velocity.y,
+                energy: b.energy,
+                is_collapsed: b.state == BoidState::Collapsed,
+                is_tasty: b.mutations.has(Mutations::TASTY),
+                in_shelter,
+            }
         }).collect();
 
-        // Predators hunt
+        // Predators pick targets independently; catches are resolved in order.
+        // Rain and snow shorten how far they can see.
+        let predator_vision = self.predator_vision.with_range_mult(weather.visibility());
+        #[cfg(not(feature = "parallel"))]
+        let catches: Vec<_> = self.predators.iter_mut()
+            .map(|p| p.hunt(&boid_data, &self.spatial_hash, &mut self.candidate_buffer, predator_vision, light, params.predator_aggression))
+            .collect();
+        #[cfg(feature = "parallel")]
+        let catches: Vec<_> = self.predators.par_iter_mut()
+            .map_init(Vec::new, |nearby, p| p.hunt(&boid_data, &self.spatial_hash, nearby, predator_vision, light, params.predator_aggression))
+            .collect();
+
         let mut caught_indices = Vec::new();
-        for predator in &mut self.predators {
-            if let Some(idx) = predator.hunt(&boid_data, &self.shelters, day_phase) {
+        for (predator, catch) in self.predators.iter_mut().zip(catches) {
+            if let Some(idx) = catch {
                 if !caught_indices.contains(&idx) {
                     caught_indices.push(idx);
                     predator.energy = (predator.energy + 45.0).min(150.0);
@@ -166,35 +304,42 @@ impl World {
             }
         }
 
-        // Remove caught boids
-        caught_indices.sort_unstable();
-        for idx in caught_indices.into_iter().rev() {
-            self.boids.swap_remove(idx);
+        // Remove caught boids, leaving their flock a warning. Removal moves
+        // other boids to new indices, so flocking needs a fresh cache.
+        if !caught_indices.is_empty() {
+            caught_indices.sort_unstable();
+            for idx in caught_indices.into_iter().rev() {
+                let b = self.boids.swap_remove(idx);
+                self.scent.deposit(Channel::Danger(b.species), b.position.x, b.position.y, 5.0);
+            }
+            self.cache_boids();
+        }
+
+        // Predators with nothing in sight follow the boid scent. Packs hold
+        // the ground they hunt over.
+        for pred in &mut self.predators {
+            pred.track(self.scent.gradient(Channel::Boid, pred.position.x, pred.position.y));
+            self.territory.mark(Owner::Pack(pred.pack_id), pred.position.x, pred.position.y);
         }
 
         // Update predators
-        self.predators.retain_mut(|pred| pred.update(self.width, self.height, &self.obstacles));
+        self.predators.retain_mut(|pred| {
+            let speed = self.terrain.zone_at(pred.position.x, pred.position.y).predator_speed();
+            pred.update(self.width, self.height, speed, &self.obstacles, &self.flow)
+        });
 
         // Get predator positions for boid flocking
         let predator_positions: Vec<_> = self.predators.iter()
             .map(|p| (p.position.x, p.position.y))
             .collect();
 
-        // Update boids - use index-based approach to avoid borrow issues
+        // Flocking only reads the boid cache and spatial hash, so forces for
+        // every boid are computed up front and applied afterwards
+        self.compute_flocking_forces(day_phase, &predator_positions);
+
         let boid_count = self.boids.len();
         for i in 0..boid_count {
-            // Get nearby boids from cache
-            self.spatial_hash.get_nearby_into(
-                self.boid_cache[i].x,
-                self.boid_cache[i].y,
-                2,
-                &mut self.nearby_buffer,
-            );
-
-            // Calculate flocking forces using cached data
-            let (ax, ay) = self.calculate_flocking_forces(i, day_phase, &predator_positions);
-
-            // Apply forces
+            let (ax, ay) = self.force_buffer[i];
             self.boids[i].acceleration.x += ax;
             self.boids[i].acceleration.y += ay;
 
@@ -215,25 +360,45 @@ impl World {
             }
         }
 
-        // Boids eat bugs - separate pass
+        self.eat_bugs();
+
+        // NOCTURNAL boids are drawn to the brightest firefly flash in reach
+        let lure_cells = (FIREFLY_LURE_RADIUS / 50.0).ceil() as i32;
         for boid in &mut self.boids {
-            for bug in &mut self.bugs {
-                let dx = boid.position.x - bug.position.x;
-                let dy = boid.position.y - bug.position.y;
-                if dx * dx + dy * dy < 144.0 {
-                    bug.energy = 0.0;
-                    boid.energy = (boid.energy + 8.0).min(boid.max_energy);
-                    boid.fatigue = (boid.fatigue - 2.0).max(0.0);
+            if boid.state != BoidState::Normal || !boid.mutations.has(Mutations::NOCTURNAL) { continue; }
+            self.bug_hash.get_nearby_into(boid.position.x, boid.position.y, lure_cells, &mut self.candidate_buffer);
+            let mut lure: Option<(f32, f32, f32)> = None;
+            for &idx in &self.candidate_buffer {
+                let bug = &self.bugs[idx];
+                if bug.energy <= 0.0 || bug.brightness < 0.1 { continue; }
+                let dx = bug.position.x - boid.position.x;
+                let dy = bug.position.y - boid.position.y;
+                let dsq = dx * dx + dy * dy;
+                if dsq < FIREFLY_LURE_RADIUS * FIREFLY_LURE_RADIUS && lure.is_none_or(|l| bug.brightness > l.2) {
+                    lure = Some((dx, dy, bug.brightness));
                 }
             }
+            if let Some((dx, dy, brightness)) = lure {
+                let d = (dx * dx + dy * dy).sqrt().max(0.001);
+                let force = 0.15 * 0.5 * brightness;
+                boid.acceleration.x += dx / d * force;
+                boid.acceleration.y += dy / d * force;
+            }
         }
 
         // Boids interact with food - separate pass
+        self.food_hash.clear();
+        for (i, food) in self.food_sources.iter().enumerate() {
+            self.food_hash.insert(i, food.0, food.1);
+        }
         for boid in &mut self.boids {
             if boid.state != BoidState::Normal || boid.fear > 0.3 {
                 continue;
             }
-            for food in &mut self.food_sources {
+            self.food_hash.get_nearby_into(boid.position.x, boid.position.y, 1, &mut self.candidate_buffer);
+            self.candidate_buffer.sort_unstable();
+            for &idx in &self.candidate_buffer {
+                let food = &mut self.food_sources[idx];
                 if food.2 <= 0.0 { continue; }
                 let dx = food.0 - boid.position.x;
                 let dy = food.1 - boid.position.y;
@@ -244,6 +409,7 @@ impl World {
                         food.2 -= 0.4;
                         boid.energy = (boid.energy + 1.0).min(boid.max_energy);
                         boid.fatigue = (boid.fatigue - 0.3).max(0.0);
+                        self.scent.deposit(Channel::Food(boid.species), food.0, food.1, 0.2);
                     } else {
                         let d = dsq.sqrt();
                         let force = 0.15 * 0.4 * hm;
@@ -254,227 +420,359 @@ impl World {
             }
         }
 
+        // Flying boids leave a trail and claim ground for their species, and
+        // panicking ones mark the danger
+        for i in 0..self.boids.len() {
+            let boid = &self.boids[i];
+            if boid.state != BoidState::Normal { continue; }
+            let (x, y, species, is_hybrid) = (boid.position.x, boid.position.y, boid.species, boid.is_hybrid);
+            let fear = self.fear_buffer[i];
+            self.scent.deposit(Channel::Boid, x, y, 0.05);
+            if !is_hybrid {
+                self.territory.mark(Owner::Species(species), x, y);
+            }
+            if fear > PANIC_FEAR {
+                self.scent.deposit(Channel::Danger(species), x, y, fear * 0.2);
+            }
+        }
+
+        // Cover lets boids get their breath back as they pass over it
+        for boid in &mut self.boids {
+            let recovery = self.terrain.zone_at(boid.position.x, boid.position.y).fatigue_recovery();
+            if boid.state == BoidState::Normal && recovery > 0.0 {
+                boid.fatigue = (boid.fatigue - recovery).max(0.0);
+            }
+        }
+
         // Update boid physics
-        self.boids.retain_mut(|boid| boid.update(self.width, self.height, day_phase));
+        self.boids.retain_mut(|boid| boid.update(self.width, self.height, light, weather, &self.obstacles, &self.flow));
 
         // Remove dead bugs
         self.bugs.retain(|bug| bug.energy > 0.0);
     }
 
-    fn calculate_flocking_forces(&self, i: usize, day_phase: f32, predator_positions: &[(f32, f32)]) -> (f32, f32) {
-        let boid = &self.boids[i];
-        if boid.state != BoidState::Normal { return (0.0, 0.0); }
-
-        let px = boid.position.x;
-        let py = boid.position.y;
-        let vx = boid.velocity.x;
-        let vy = boid.velocity.y;
-        let species = boid.species;
-        let is_hybrid = boid.is_hybrid;
-        let sociability = boid.sociability;
-        let bravery = boid.bravery;
-        let is_bullied = boid.mutations.has(Mutations::BULLIED);
-
-        const MAX_SPEED: f32 = 4.0;
-        const MAX_FORCE: f32 = 0.15;
-
-        let mut sep_x = 0.0f32;
-        let mut sep_y = 0.0f32;
-        let mut ali_x = 0.0f32;
-        let mut ali_y = 0.0f32;
-        let mut coh_x = 0.0f32;
-        let mut coh_y = 0.0f32;
-        let mut sep_ct = 0u32;
-        let mut ali_ct = 0u32;
-        let mut coh_ct = 0u32;
-
-        let coh_radius = 50.0 * (1.0 + (1.0 - day_phase) * 0.5 + sociability * 0.3);
-        let coh_radius_sq = coh_radius * coh_radius;
-
-        for &idx in &self.nearby_buffer {
-            if idx == i || idx >= self.boid_cache.len() { continue; }
-            let other = &self.boid_cache[idx];
-            if other.state != 0 { continue; } // Not normal
-
-            let dx = px - other.x;
-            let dy = py - other.y;
-            let dsq = dx * dx + dy * dy;
+    /// How far to gather neighbours. The nearest flock-mates may be anywhere
+    /// in view, so topological mode gathers out to the longest vision range.
+    fn flocking_reach(&self) -> f32 {
+        let reach = self.behaviours.reach();
+        match self.neighbour_mode {
+            NeighbourMode::Metric => reach,
+            NeighbourMode::Topological(_) => self.vision.iter().map(Vision::range).fold(reach, f32::max),
+        }
+    }
 
-            if dsq < 625.0 {
-                let inv = 1.0 / (dsq + 0.001);
-                sep_x += dx * inv;
-                sep_y += dy * inv;
-                sep_ct += 1;
-            }
+    fn environment<'a>(&'a self, day_phase: f32, predators: &'a [(f32, f32)]) -> Environment<'a> {
+        Environment {
+            width: self.width,
+            height: self.height,
+            time: self.time,
+            day_phase,
+            predators,
+            obstacles: &self.obstacles,
+            shelters: &self.shelters,
+            scent: &self.scent,
+            territory: &self.territory,
+        }
+    }
 
-            let can_flock = is_hybrid || other.is_hybrid || other.species == species;
-            if can_flock {
-                if dsq < 2500.0 {
-                    ali_x += other.vx;
-                    ali_y += other.vy;
-                    ali_ct += 1;
-                }
-                if dsq < coh_radius_sq {
-                    coh_x += other.x;
-                    coh_y += other.y;
-                    coh_ct += 1;
+    /// Boids eat bugs within reach. Each bug feeds only the first boid to
+    /// reach it.
+    fn eat_bugs(&mut self) {
+        self.bug_hash.clear();
+        for (i, bug) in self.bugs.iter().enumerate() {
+            self.bug_hash.insert(i, bug.position.x, bug.position.y);
+        }
+        for boid in &mut self.boids {
+            self.bug_hash.get_nearby_into(boid.position.x, boid.position.y, 1, &mut self.candidate_buffer);
+            for &idx in &self.candidate_buffer {
+                let bug = &mut self.bugs[idx];
+                if bug.energy <= 0.0 { continue; }
+                let dx = boid.position.x - bug.position.x;
+                let dy = boid.position.y - bug.position.y;
+                if dx * dx + dy * dy < 144.0 {
+                    bug.energy = 0.0;
+                    boid.energy = (boid.energy + 8.0).min(boid.max_energy);
+                    boid.fatigue = (boid.fatigue - 2.0).max(0.0);
+                    self.scent.deposit(Channel::Food(boid.species), bug.position.x, bug.position.y, 1.0);
                 }
             }
         }
+    }
 
-        let mut ax = 0.0f32;
-        let mut ay = 0.0f32;
-
-        // Apply separation
-        if sep_ct > 0 {
-            let mut sx = sep_x / sep_ct as f32;
-            let mut sy = sep_y / sep_ct as f32;
-            let sm = (sx * sx + sy * sy).sqrt();
-            if sm > 0.0 { sx /= sm; sy /= sm; }
-            sx = sx * MAX_SPEED - vx;
-            sy = sy * MAX_SPEED - vy;
-            let sm = (sx * sx + sy * sy).sqrt();
-            if sm > MAX_FORCE { sx = sx / sm * MAX_FORCE; sy = sy / sm * MAX_FORCE; }
-            ax += sx * 1.8;
-            ay += sy * 1.8;
-        }
-
-        // Apply alignment
-        if ali_ct > 0 {
-            let mut lx = ali_x / ali_ct as f32;
-            let mut ly = ali_y / ali_ct as f32;
-            let lm = (lx * lx + ly * ly).sqrt();
-            if lm > 0.0 { lx /= lm; ly /= lm; }
-            lx = lx * MAX_SPEED - vx;
-            ly = ly * MAX_SPEED - vy;
-            let lm = (lx * lx + ly * ly).sqrt();
-            if lm > MAX_FORCE { lx = lx / lm * MAX_FORCE; ly = ly / lm * MAX_FORCE; }
-            ax += lx;
-            ay += ly;
-        }
-
-        // Apply cohesion
-        if coh_ct > 0 {
-            let mut cx = coh_x / coh_ct as f32 - px;
-            let mut cy = coh_y / coh_ct as f32 - py;
-            let cm = (cx * cx + cy * cy).sqrt();
-            if cm > 0.0 { cx /= cm; cy /= cm; }
-            cx = cx * MAX_SPEED - vx;
-            cy = cy * MAX_SPEED - vy;
-            let cm = (cx * cx + cy * cy).sqrt();
-            if cm > MAX_FORCE { cx = cx / cm * MAX_FORCE; cy = cy / cm * MAX_FORCE; }
-            let sm = 1.0 + sociability * 0.3;
-            ax += cx * sm;
-            ay += cy * sm;
-        }
-
-        // Flee from predators
-        let mut flee_x = 0.0f32;
-        let mut flee_y = 0.0f32;
-        let mut fear: f32 = if is_bullied { 0.3 } else { 0.0 };
-        let flee_radius = 100.0 + (1.0 - bravery) * 50.0;
-        let flee_radius_sq = flee_radius * flee_radius;
+    /// Rebuild the spatial hash and the snapshot of every boid that hunting
+    /// and flocking read
+    fn cache_boids(&mut self) {
+        self.spatial_hash.clear();
+        self.boid_cache.clear();
+        for (i, boid) in self.boids.iter().enumerate() {
+            self.spatial_hash.insert(i, boid.position.x, boid.position.y);
+            self.boid_cache.push(BoidCache {
+                x: boid.position.x,
+                y: boid.position.y,
+                vx: boid.velocity.x,
+                vy: boid.velocity.y,
+                species: boid.species,
+                is_hybrid: boid.is_hybrid,
+                state: match boid.state {
+                    BoidState::Normal => 0,
+                    BoidState::Perching => 1,
+                    BoidState::Collapsed => 2,
+                    BoidState::Fishing => 3,
+                },
+            });
+        }
+    }
 
-        // Check if in shelter
-        let mut in_shelter = false;
-        for &(sx, sy, sr) in &self.shelters {
-            let dx = sx - px;
-            let dy = sy - py;
-            if dx * dx + dy * dy < sr * sr {
-                in_shelter = true;
-                fear = if is_bullied { 0.2 } else { 0.0 };
-                break;
-            }
+    #[cfg(not(feature = "parallel"))]
+    fn compute_flocking_forces(&mut self, day_phase: f32, predator_positions: &[(f32, f32)]) {
+        let mut nearby = std::mem::take(&mut self.nearby_buffer);
+        let mut neighbours = std::mem::take(&mut self.neighbourhood);
+        let mut forces = std::mem::take(&mut self.force_buffer);
+        let mut fears = std::mem::take(&mut self.fear_buffer);
+        let reach = self.flocking_reach();
+        let env = self.environment(day_phase, predator_positions);
+        // Two cells cover the standard flocking radii; wider custom radii need a wider search
+        let cells = ((reach / CELL_SIZE).ceil() as i32).max(2);
+        forces.clear();
+        fears.clear();
+        for i in 0..self.boids.len() {
+            let c = &self.boid_cache[i];
+            self.spatial_hash.get_nearby_into(c.x, c.y, cells, &mut nearby);
+            let (force, fear) = self.calculate_flocking_forces(i, &env, reach, &nearby, &mut neighbours);
+            forces.push(force);
+            fears.push(fear);
         }
+        self.nearby_buffer = nearby;
+        self.neighbourhood = neighbours;
+        self.force_buffer = forces;
+        self.fear_buffer = fears;
+    }
 
-        if !in_shelter {
-            for &(pred_x, pred_y) in predator_positions {
-                let dx = px - pred_x;
-                let dy = py - pred_y;
-                let dsq = dx * dx + dy * dy;
-                if dsq < flee_radius_sq {
-                    let d = dsq.sqrt();
-                    let inv = 1.0 / (d + 0.001);
-                    flee_x += dx * inv;
-                    flee_y += dy * inv;
-                    fear = fear.max(1.0 - d / flee_radius);
-                }
+    /// Same as the serial version, split across the rayon pool. Each boid's
+    /// force depends only on tick-start state, so results are identical.
+    #[cfg(feature = "parallel")]
+    fn compute_flocking_forces(&mut self, day_phase: f32, predator_positions: &[(f32, f32)]) {
+        let mut forces = std::mem::take(&mut self.force_buffer);
+        let mut fears = std::mem::take(&mut self.fear_buffer);
+        let reach = self.flocking_reach();
+        let env = self.environment(day_phase, predator_positions);
+        // Two cells cover the standard flocking radii; wider custom radii need a wider search
+        let cells = ((reach / CELL_SIZE).ceil() as i32).max(2);
+        (0..self.boids.len())
+            .into_par_iter()
+            .map_init(
+                || (Vec::with_capacity(100), Neighbourhood::default()),
+                |(nearby, neighbours), i| {
+                    let c = &self.boid_cache[i];
+                    self.spatial_hash.get_nearby_into(c.x, c.y, cells, nearby);
+                    self.calculate_flocking_forces(i, &env, reach, nearby, neighbours)
+                },
+            )
+            .unzip_into_vecs(&mut forces, &mut fears);
+        self.force_buffer = forces;
+        self.fear_buffer = fears;
+    }
+
+    /// Run the behaviour pipeline for boid `i` against the neighbours within
+    /// `reach` among those the spatial hash returned. Also returns the boid's
+    /// fear, which later passes reuse.
+    fn calculate_flocking_forces(&self, i: usize, env: &Environment, reach: f32, nearby: &[usize], neighbours: &mut Neighbourhood) -> ((f32, f32), f32) {
+        let boid = &self.boids[i];
+        if boid.state != BoidState::Normal { return ((0.0, 0.0), 0.0); }
+
+        let (in_shelter, fear) = self.sense_threat(boid, env.predators);
+        let agent = Agent::new(boid, self.vision_for(boid.species), in_shelter, fear);
+
+        let reach_sq = reach * reach;
+        neighbours.refill(nearby.len(), |slots| {
+            let mut n = 0;
+            for &idx in nearby {
+                let Some(other) = self.boid_cache.get(idx) else { continue };
+                let same_flock = agent.is_hybrid || other.is_hybrid || other.species == agent.species;
+                let slot = &mut slots[n];
+                *slot = Neighbour::new(&agent, Vec2::new(other.x, other.y), Vec2::new(other.vx, other.vy), same_flock);
+                // Keep only other normal-state boids within reach that are in view
+                let seen = agent.vision.sees(agent.velocity, slot.offset.mult(-1.0), slot.dist_sq);
+                n += (idx != i && other.state == 0 && slot.dist_sq < reach_sq && seen) as usize;
             }
+            n
+        });
+        neighbours.select_nearest_flock(self.neighbour_mode);
+
+        let a = self.behaviours.steer(&agent, neighbours, env);
+        ((a.x, a.y), fear)
+    }
+
+    /// Whether a boid is in a shelter, and how frightened it is by predators
+    /// within its flee radius. Bullied boids are never fully at ease.
+    fn sense_threat(&self, boid: &Boid, predator_positions: &[(f32, f32)]) -> (bool, f32) {
+        let is_bullied = boid.mutations.has(Mutations::BULLIED);
+        let (px, py) = (boid.position.x, boid.position.y);
+        let in_shelter = self.shelters.iter().any(|&(sx, sy, sr)| {
+            let dx = sx - px;
+            let dy = sy - py;
+            dx * dx + dy * dy < sr * sr
+        });
+        if in_shelter {
+            return (true, if is_bullied { 0.2 } else { 0.0 });
         }
 
-        if flee_x != 0.0 || flee_y != 0.0 {
-            let fm = (flee_x * flee_x + flee_y * flee_y).sqrt();
-            if fm > 0.0 { flee_x /= fm; flee_y /= fm; }
-            let fs = MAX_SPEED * (1.5 + bravery * 0.3);
-            flee_x = flee_x * fs - vx;
-            flee_y = flee_y * fs - vy;
-            let fm = (flee_x * flee_x + flee_y * flee_y).sqrt();
-            let fl = MAX_FORCE * 3.0;
-            if fm > fl { flee_x = flee_x / fm * fl; flee_y = flee_y / fm * fl; }
-            let flee_mult = if fear > 0.3 { 4.0 } else { 1.0 } * (4.0 - bravery);
-            ax += flee_x * flee_mult;
-            ay += flee_y * flee_mult;
-        }
-
-        // Avoid obstacles
-        let mut obs_x = 0.0f32;
-        let mut obs_y = 0.0f32;
-        let obs_radius_sq = if fear > 0.3 { 6400.0 } else { 2500.0 };
-
-        for &(ox, oy) in &self.obstacles {
-            let dx = px - ox;
-            let dy = py - oy;
+        let mut fear: f32 = if is_bullied { 0.3 } else { 0.0 };
+        let flee_radius = FLEE_RADIUS + (1.0 - boid.bravery) * FLEE_RADIUS * 0.5;
+        let vision = self.vision_for(boid.species);
+        for &(pred_x, pred_y) in predator_positions {
+            let dx = px - pred_x;
+            let dy = py - pred_y;
             let dsq = dx * dx + dy * dy;
-            if dsq < obs_radius_sq {
-                let inv = 1.0 / (dsq + 0.001);
-                obs_x += dx * inv;
-                obs_y += dy * inv;
+            if dsq < flee_radius * flee_radius && vision.sees(boid.velocity, Vec2::new(-dx, -dy), dsq) {
+                fear = fear.max(1.0 - dsq.sqrt() / flee_radius);
             }
         }
+        (false, fear)
+    }
 
-        if obs_x != 0.0 || obs_y != 0.0 {
-            let om = (obs_x * obs_x + obs_y * obs_y).sqrt();
-            if om > 0.0 { obs_x /= om; obs_y /= om; }
-            let os = MAX_SPEED * if fear > 0.3 { 1.5 } else { 1.0 };
-            obs_x = obs_x * os - vx;
-            obs_y = obs_y * os - vy;
-            let om = (obs_x * obs_x + obs_y * obs_y).sqrt();
-            let ol = MAX_FORCE * if fear > 0.3 { 3.0 } else { 2.0 };
-            if om > ol { obs_x = obs_x / om * ol; obs_y = obs_y / om * ol; }
-            let obs_mult = if fear > 0.3 { 3.0 } else { 1.0 };
-            ax += obs_x * obs_mult;
-            ay += obs_y * obs_mult;
-        }
-
-        (ax, ay)
+    pub fn vision_for(&self, species: u8) -> Vision {
+        self.vision[(species as usize).min(self.vision.len() - 1)]
     }
 
     pub fn add_boid(&mut self, x: f32, y: f32, species: u8) {
         self.boids.push(Boid::new(x, y, species, false));
     }
 
+    /// Add a loose cluster of `count` boids of random species around a point
+    pub fn add_boids(&mut self, x: f32, y: f32, count: u32) {
+        for i in 0..count {
+            let ox = (i as f32 - count as f32 / 2.0) * 5.0 + (rand_f32() - 0.5) * 20.0;
+            let oy = (rand_f32() - 0.5) * 20.0;
+            let species = if rand_f32() > 0.5 { 0 } else { 1 };
+            self.add_boid(x + ox, y + oy, species);
+        }
+    }
+
+    /// Add `count` predators at random points in open air, each in its own pack
+    pub fn add_random_predators(&mut self, count: u32) {
+        for _ in 0..count {
+            let p = self.spawn_point(Spawn::Flyer);
+            self.add_predator(p.x, p.y);
+        }
+    }
+
+    /// Where the terrain lets `spawn` appear, or anywhere if it allows
+    /// nowhere (boids and predators have to start somewhere)
+    fn spawn_point(&self, spawn: Spawn) -> Vec2 {
+        self.terrain.random_point(spawn, rand_f32)
+            .unwrap_or_else(|| Vec2::new(rand_f32() * self.width, rand_f32() * self.height))
+    }
+
+    /// Replace the terrain map. It is stretched over the world whatever its
+    /// size in cells.
+    pub fn set_terrain(&mut self, terrain: TerrainMap) {
+        self.terrain = terrain;
+    }
+
     pub fn add_predator(&mut self, x: f32, y: f32) {
         self.predators.push(Predator::new(x, y, self.next_pack_id, 0));
         self.next_pack_id += 1;
     }
 
-    pub fn add_obstacle(&mut self, x: f32, y: f32) {
-        self.obstacles.push((x, y));
+    pub fn add_obstacle(&mut self, x: f32, y: f32, radius: f32) {
+        self.obstacles.add(x, y, radius);
+    }
+
+    /// Remove obstacles, walls and polygons within `dist` of the point
+    pub fn remove_obstacle(&mut self, x: f32, y: f32, dist: f32) {
+        self.obstacles.remove_near(x, y, dist);
+    }
+
+    pub fn add_wall(&mut self, x1: f32, y1: f32, x2: f32, y2: f32) {
+        self.obstacles.add_wall(x1, y1, x2, y2);
+    }
+
+    /// Add the convex hull of `points` as a solid obstacle. Returns false if
+    /// the points do not enclose an area.
+    pub fn add_polygon(&mut self, points: &[Vec2]) -> bool {
+        self.obstacles.add_polygon(points)
+    }
+
+    /// Add a steering behaviour after the built-in ones, or swap out the
+    /// behaviour with the same name (built-ins included). It is steered with `weight` and `radius`,
+    /// both adjustable later through `behaviours`.
+    pub fn register_behaviour(&mut self, behaviour: impl Behaviour + 'static, weight: f32, radius: f32) {
+        self.behaviours.push(Box::new(behaviour), weight, radius);
     }
 
     pub fn add_food(&mut self, x: f32, y: f32) {
         self.food_sources.push((x, y, 100.0));
     }
 
-    pub fn get_stats(&self) -> (u32, u32, u32, f32) {
+    pub fn season(&self) -> Season {
+        Season::from_time(self.season_time)
+    }
+
+    /// How far through the current season the world is, 0..1
+    pub fn season_progress(&self) -> f32 {
+        Season::progress(self.season_time)
+    }
+
+    /// The current season's `breeding_mult`. Boids are not bred inside the
+    /// engine, so hosts that breed them scale their birth chance by this.
+    pub fn breeding_mult(&self) -> f32 {
+        self.seasons.get(self.season()).breeding_mult
+    }
+
+    /// 0 = midnight, 1 = noon
+    pub fn day_phase(&self) -> f32 {
+        (self.day_time.sin() + 1.0) / 2.0
+    }
+
+    /// Daylight after cloud cover, 0 = dark, 1 = clear noon. Drives boid
+    /// speed, predator aggression and nocturnal bonuses.
+    pub fn light_level(&self) -> f32 {
+        self.day_phase() * self.weather.current.light()
+    }
+
+    /// Clock time as a fraction of the day: 0 = midnight, 0.25 = dawn,
+    /// 0.5 = noon, 0.75 = dusk
+    pub fn time_of_day(&self) -> f32 {
+        (self.day_time / TAU + 0.25).rem_euclid(1.0)
+    }
+
+    /// Jump the clock to a fraction of the day (see `time_of_day`). Does not
+    /// raise dawn or dusk events.
+    pub fn set_time_of_day(&mut self, fraction: f32) {
+        self.day_time = (fraction.rem_euclid(1.0) - 0.25) * TAU;
+    }
+
+    /// Ticks in a full day and night, before the season's day length
+    pub fn day_length(&self) -> f32 {
+        TAU / self.day_step
+    }
+
+    pub fn set_day_length(&mut self, ticks: f32) {
+        self.day_step = TAU / ticks.max(1.0);
+    }
+
+    /// Dawns and dusks since the last call, oldest first
+    pub fn take_day_events(&mut self) -> Vec<DayEvent> {
+        std::mem::take(&mut self.day_events)
+    }
+
+    /// (boids, predators, bugs, day_phase, weather)
+    pub fn get_stats(&self) -> (u32, u32, u32, f32, Weather) {
         (
             self.boids.len() as u32,
             self.predators.len() as u32,
             self.bugs.len() as u32,
-            (self.day_time.sin() + 1.0) / 2.0,
+            self.day_phase(),
+            self.weather.current,
         )
     }
 
+    /// Standalone SVG snapshot of the current state
+    pub fn export_svg(&self, options: SvgOptions) -> String {
+        svg::export_svg(self, options)
+    }
+
     /// Returns flat array: [x, y, vx, vy, hue, energy, max_energy, size_mult, mutations, fear, state, ...]
     pub fn get_boid_render_data(&self) -> Vec<f32> {
         let mut data = Vec::with_capacity(self.boids.len() * 11);
@@ -514,21 +812,109 @@ impl World {
         data
     }
 
-    /// Returns flat array: [x, y, hue, size, ...]
+    /// Returns flat array of previous-step positions: [prev_x, prev_y, ...]
+    /// in the same order as `get_boid_render_data`
+    pub fn get_boid_prev_data(&self) -> Vec<f32> {
+        self.prev_positions(self.boids.iter().map(|b| (b.prev_position, b.position)))
+    }
+
+    /// Returns flat array of previous-step positions: [prev_x, prev_y, ...]
+    /// in the same order as `get_predator_render_data`
+    pub fn get_predator_prev_data(&self) -> Vec<f32> {
+        self.prev_positions(self.predators.iter().map(|p| (p.prev_position, p.position)))
+    }
+
+    /// Returns flat array of previous-step positions: [prev_x, prev_y, ...]
+    /// in the same order as `get_bug_render_data`
+    pub fn get_bug_prev_data(&self) -> Vec<f32> {
+        self.prev_positions(self.bugs.iter().map(|b| (b.prev_position, b.position)))
+    }
+
+    /// Previous positions, shifted by a world width/height when the entity
+    /// wrapped across an edge so that lerping toward the current position
+    /// slides off the edge instead of sweeping across the screen.
+    fn prev_positions(&self, entities: impl ExactSizeIterator<Item = (Vec2, Vec2)>) -> Vec<f32> {
+        let mut data = Vec::with_capacity(entities.len() * 2);
+        for (prev, cur) in entities {
+            let mut px = prev.x;
+            let mut py = prev.y;
+            if cur.x - px > self.width * 0.5 { px += self.width; }
+            else if px - cur.x > self.width * 0.5 { px -= self.width; }
+            if cur.y - py > self.height * 0.5 { py += self.height; }
+            else if py - cur.y > self.height * 0.5 { py -= self.height; }
+            data.push(px);
+            data.push(py);
+        }
+        data
+    }
+
+    /// Every obstacle shape, each record starting with its kind:
+    /// circle `[0, x, y, radius]`, wall `[1, x1, y1, x2, y2]`,
+    /// polygon `[2, n, x1, y1, ..., xn, yn]`
+    pub fn get_geometry_render_data(&self) -> Vec<f32> {
+        let mut data = Vec::new();
+        for o in self.obstacles.as_slice() {
+            data.extend_from_slice(&[0.0, o.position.x, o.position.y, o.radius]);
+        }
+        for w in self.obstacles.walls() {
+            data.extend_from_slice(&[1.0, w.a.x, w.a.y, w.b.x, w.b.y]);
+        }
+        for poly in self.obstacles.polygons() {
+            data.extend_from_slice(&[2.0, poly.points().len() as f32]);
+            for p in poly.points() {
+                data.extend_from_slice(&[p.x, p.y]);
+            }
+        }
+        data
+    }
+
+    /// Flow field sampled on a grid of `spacing` px cells.
+    /// Returns flat array: [x, y, fx, fy, ...]
+    pub fn get_flow_render_data(&self, spacing: f32) -> Vec<f32> {
+        self.flow.sample_grid(self.width, self.height, spacing)
+    }
+
+    /// Terrain map stretched over the world.
+    /// Returns flat array: [cols, rows, zone, zone, ...] with zones row by row
+    pub fn get_terrain_render_data(&self) -> Vec<f32> {
+        self.terrain.render_data()
+    }
+
+    /// Share of the world boid species `species` holds, 0..1
+    pub fn territory_share(&self, species: u8) -> f32 {
+        self.territory.share(Owner::Species(species))
+    }
+
+    /// Territory ownership, owner -1 for unclaimed or see `Owner::code`.
+    /// Returns flat array: [cols, rows, owner, strength, ...] row by row
+    pub fn get_territory_render_data(&self) -> Vec<f32> {
+        self.territory.render_data()
+    }
+
+    /// Scent channel (see `Channel::index`) as a heatmap.
+    /// Returns flat array: [cols, rows, value, value, ...] row by row
+    pub fn get_scent_render_data(&self, channel: Channel) -> Vec<f32> {
+        self.scent.render_data(channel)
+    }
+
+    /// Brightness is a firefly's glow, 0..1, and always 0 for common bugs.
+    /// Returns flat array: [x, y, hue, size, brightness, ...]
     pub fn get_bug_render_data(&self) -> Vec<f32> {
-        let mut data = Vec::with_capacity(self.bugs.len() * 4);
+        let mut data = Vec::with_capacity(self.bugs.len() * 5);
         for bug in &self.bugs {
             data.push(bug.position.x);
             data.push(bug.position.y);
             data.push(bug.hue);
             data.push(bug.size);
+            data.push(bug.brightness);
         }
         data
     }
 }
 
+static mut SEED: u32 = 99999;
+
 fn rand_f32() -> f32 {
-    static mut SEED: u32 = 99999;
     unsafe {
         SEED ^= SEED << 13;
         SEED ^= SEED >> 17;
@@ -536,3 +922,288 @@ fn rand_f32() -> f32 {
         (SEED as f32) / (u32::MAX as f32)
     }
 }
+
+pub fn set_seed(seed: u32) {
+    unsafe {
+        SEED = if seed == 0 { 99999 } else { seed };
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type Point = (f32, f32);
+
+    /// Start and velocity for one mover across each edge of a 200 x 160
+    /// world, with where it comes back in and where its previous position
+    /// should be reported
+    const EDGES: [(Point, Point, Point, Point); 4] = [
+        ((199.5, 80.0), (3.0, 0.0), (0.0, 80.0), (-0.5, 80.0)),
+        ((0.5, 80.0), (-3.0, 0.0), (200.0, 80.0), (200.5, 80.0)),
+        ((100.0, 159.5), (0.0, 3.0), (100.0, 0.0), (100.0, -0.5)),
+        ((100.0, 0.5), (0.0, -3.0), (100.0, 160.0), (100.0, 160.5)),
+    ];
+
+    fn edge_world() -> World {
+        let mut world = World::new(200.0, 160.0, 0);
+        world.obstacles.clear();
+        world.shelters.clear();
+        world
+    }
+
+    fn check_wrapped(what: &str, cur: &[f32], stride: usize, prev: &[f32]) {
+        assert_eq!(cur.len() / stride, EDGES.len(), "{what}: lost an entity");
+        for (i, &(_, _, wrapped, unwrapped)) in EDGES.iter().enumerate() {
+            // Movement along the edge is free, so only the crossing axis is exact
+            let (c, p) = ((cur[i * stride], cur[i * stride + 1]), (prev[i * 2], prev[i * 2 + 1]));
+            if i < 2 {
+                assert_eq!((c.0, p.0), (wrapped.0, unwrapped.0), "{what} {i}");
+                assert!((p.1 - unwrapped.1).abs() < 4.0, "{what} {i}: {p:?}");
+            } else {
+                assert_eq!((c.1, p.1), (wrapped.1, unwrapped.1), "{what} {i}");
+                assert!((p.0 - unwrapped.0).abs() < 4.0, "{what} {i}: {p:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn prev_data_is_unwrapped_across_edges() {
+        let _rng = crate::rng_lock();
+
+        let mut world = edge_world();
+        for &((x, y), (vx, vy), _, _) in &EDGES {
+            world.add_boid(x, y, 0);
+            world.boids.last_mut().unwrap().velocity = Vec2::new(vx, vy);
+        }
+        world.tick(0.0, 0.0, 0, 0.0);
+        check_wrapped("boid", &world.get_boid_render_data(), 11, &world.get_boid_prev_data());
+
+        let mut world = edge_world();
+        for &((x, y), (vx, vy), _, _) in &EDGES {
+            world.add_predator(x, y);
+            world.predators.last_mut().unwrap().velocity = Vec2::new(vx, vy);
+        }
+        world.tick(0.0, 0.0, 0, 0.0);
+        check_wrapped("predator", &world.get_predator_render_data(), 7, &world.get_predator_prev_data());
+
+        let mut world = edge_world();
+        world.bug_mult = 0.0;
+        for &((x, y), (vx, vy), _, _) in &EDGES {
+            let mut bug = Bug::new(x, y);
+            bug.velocity = Vec2::new(vx, vy);
+            world.bugs.push(bug);
+        }
+        world.tick(0.0, 0.0, 0, 0.0);
+        check_wrapped("bug", &world.get_bug_render_data(), 5, &world.get_bug_prev_data());
+    }
+
+    #[test]
+    fn food_on_the_standard_map_grows_in_the_old_band() {
+        let _rng = crate::rng_lock();
+        crate::set_seed(7);
+        let (w, h) = (900.0, 600.0);
+        let mut world = World::new(w, h, 0);
+        for season in Season::ALL {
+            world.seasons.get_mut(season).food_chance = 1.0;
+        }
+
+        let mut spawned = 0;
+        for _ in 0..300 {
+            world.set_time_of_day(0.5);
+            world.tick(0.0, 0.0, 0, 0.0);
+            for &(x, y, _) in &world.food_sources {
+                assert!((50.0..=w - 50.0).contains(&x), "x {x}");
+                assert!((50.0..=h - 120.0).contains(&y), "y {y}");
+            }
+            spawned += world.food_sources.len();
+            world.food_sources.clear();
+        }
+        assert!(spawned > 250, "only {spawned} food spawned");
+    }
+
+    #[test]
+    fn a_bug_reached_by_two_boids_is_eaten_once() {
+        let _rng = crate::rng_lock();
+        let mut world = edge_world();
+        world.bug_mult = 0.0;
+        world.set_time_of_day(0.5);
+        world.bugs.push(Bug::new(100.0, 80.0));
+        for x in [96.0, 104.0] {
+            world.add_boid(x, 80.0, 0);
+            world.boids.last_mut().unwrap().energy = 50.0;
+        }
+        world.tick(0.0, 0.0, 0, 0.0);
+
+        assert!(world.bugs.is_empty());
+        let fed: Vec<_> = world.boids.iter().map(|b| b.energy > 55.0).collect();
+        assert_eq!(fed, [true, false]);
+    }
+
+    #[test]
+    fn bug_eating_matches_a_linear_scan() {
+        let _rng = crate::rng_lock();
+        crate::set_seed(5);
+        let mut world = World::new(600.0, 400.0, 0);
+        // Crowded enough that most bugs are in reach of several boids,
+        // including across cell borders and off the world's edges
+        for i in 0..150 {
+            world.add_boid(rand_f32() * 640.0 - 20.0, rand_f32() * 440.0 - 20.0, (i % 2) as u8);
+        }
+        world.bugs.clear();
+        for _ in 0..200 {
+            world.bugs.push(Bug::new(rand_f32() * 640.0 - 20.0, rand_f32() * 440.0 - 20.0));
+        }
+        for boid in &mut world.boids {
+            boid.energy = 0.0;
+        }
+
+        let mut eaten_by = vec![None; world.bugs.len()];
+        let mut meals = vec![0; world.boids.len()];
+        for (i, boid) in world.boids.iter().enumerate() {
+            for (b, bug) in world.bugs.iter().enumerate() {
+                if eaten_by[b].is_none() && boid.position.dist_sq(bug.position) < 144.0 {
+                    eaten_by[b] = Some(i);
+                    meals[i] += 1;
+                }
+            }
+        }
+        assert!(meals.iter().filter(|&&m| m > 0).count() > 20);
+
+        world.eat_bugs();
+        let eaten: Vec<_> = world.bugs.iter().map(|b| b.energy <= 0.0).collect();
+        assert_eq!(eaten, eaten_by.iter().map(Option::is_some).collect::<Vec<_>>());
+        let fed: Vec<_> = world.boids.iter().map(|b| (b.energy / 8.0).round() as u32).collect();
+        assert_eq!(fed, meals);
+    }
+
+    #[test]
+    fn dawn_and_dusk_fire_once_a_day_where_light_crosses_the_threshold() {
+        let _rng = crate::rng_lock();
+        let mut world = edge_world();
+        for season in Season::ALL {
+            world.seasons.get_mut(season).day_length = 1.0;
+        }
+        world.set_day_length(1000.0);
+        world.set_time_of_day(0.0);
+
+        let mut events = Vec::new();
+        for t in 1..=2000 {
+            world.tick(0.0, 0.0, 0, 0.0);
+            events.extend(world.take_day_events().into_iter().map(|e| (t, e)));
+        }
+        // Day is phase > DAY_THRESHOLD, reached a little before 0.25 of the day
+        let lead = (1.0 - 2.0 * DAY_THRESHOLD).asin() / TAU * 1000.0;
+        let expected = [(250.0 - lead, DayEvent::Dawn), (750.0 + lead, DayEvent::Dusk)];
+        assert_eq!(events.len(), 4, "{events:?}");
+        for (i, &(t, event)) in events.iter().enumerate() {
+            let (at, kind) = expected[i % 2];
+            assert_eq!(event, kind);
+            assert!((t as f32 - (at + 1000.0 * (i / 2) as f32)).abs() <= 1.0, "{event:?} at tick {t}");
+        }
+
+        world.set_time_of_day(0.5);
+        assert!(world.take_day_events().is_empty());
+    }
+
+    #[test]
+    fn light_follows_the_sun_and_the_clouds() {
+        let _rng = crate::rng_lock();
+        let mut world = edge_world();
+        world.weather.auto = false;
+        world.weather.current = Weather::Clear;
+        for (time, light) in [(0.0, 0.0), (0.25, 0.5), (0.5, 1.0), (0.75, 0.5)] {
+            world.set_time_of_day(time);
+            assert!((world.time_of_day() - time).abs() < 1e-5);
+            assert!((world.light_level() - light).abs() < 1e-5, "{time}: {}", world.light_level());
+            assert_eq!(world.get_stats().3, world.day_phase());
+        }
+        world.weather.current = Weather::Storm;
+        assert!((world.light_level() - 0.5 * 0.5).abs() < 1e-5);
+        world.set_time_of_day(0.5);
+        assert!((world.light_level() - 0.5).abs() < 1e-5);
+    }
+
+    #[test]
+    fn flocking_reads_the_boids_left_after_catches() {
+        let _rng = crate::rng_lock();
+        let mut world = World::new(600.0, 400.0, 0);
+        world.obstacles.clear();
+        world.shelters.clear();
+        for i in 0..30 {
+            world.add_boid(40.0 + (i % 10) as f32 * 50.0, 80.0 + (i / 10) as f32 * 100.0, (i % 2) as u8);
+        }
+        for i in 0..3 {
+            world.add_predator(40.0 + i as f32 * 200.0, 80.0);
+        }
+
+        let mut caught = 0;
+        for t in 0..200 {
+            let before = world.boids.len();
+            world.tick(0.0, 0.0, 0, 0.0);
+            caught += before - world.boids.len();
+            // Flocking ran on this cache, and each boid set out from where it says
+            assert_eq!(world.boid_cache.len(), world.boids.len(), "tick {t}");
+            for (i, (c, b)) in world.boid_cache.iter().zip(&world.boids).enumerate() {
+                assert_eq!((c.x, c.y), (b.prev_position.x, b.prev_position.y), "tick {t}: boid {i}");
+            }
+            if caught >= 3 { break; }
+        }
+        assert!(caught > 0, "no boid was caught");
+    }
+}
+
+#[cfg(all(test, feature = "parallel"))]
+mod parallel_tests {
+    use super::*;
+
+    #[test]
+    fn parallel_matches_serial() {
+        let _rng = crate::rng_lock();
+        let mut world = World::new(900.0, 450.0, 150);
+        for i in 0..4 {
+            world.add_predator(150.0 + i as f32 * 200.0, 200.0);
+        }
+        for t in 0..120 {
+            world.tick(450.0, 225.0, 0, 0.0);
+
+            let day_phase = (world.day_time.sin() + 1.0) / 2.0;
+            let predators: Vec<_> = world.predators.iter().map(|p| (p.position.x, p.position.y)).collect();
+            world.compute_flocking_forces(day_phase, &predators);
+            let reach = world.flocking_reach();
+            let cells = ((reach / CELL_SIZE).ceil() as i32).max(2);
+            let env = world.environment(day_phase, &predators);
+            let mut neighbours = Neighbourhood::default();
+            let serial: Vec<_> = (0..world.boids.len()).map(|i| {
+                let c = &world.boid_cache[i];
+                let nearby = world.spatial_hash.get_nearby(c.x, c.y, cells);
+                world.calculate_flocking_forces(i, &env, reach, &nearby, &mut neighbours)
+            }).collect();
+            let (forces, fears): (Vec<_>, Vec<_>) = serial.into_iter().unzip();
+            assert_eq!(forces, world.force_buffer, "tick {t}: flocking forces differ");
+            assert_eq!(fears, world.fear_buffer, "tick {t}: fear differs");
+
+            let prey: Vec<_> = world.boids.iter().map(|b| Prey {
+                x: b.position.x,
+                y: b.position.y,
+                vx: b.velocity.x,
+                vy: b.velocity.y,
+                energy: b.energy,
+                is_collapsed: b.state == BoidState::Collapsed,
+                is_tasty: b.mutations.has(Mutations::TASTY),
+                in_shelter: false,
+            }).collect();
+            let (mut a, mut b) = (world.predators.clone(), world.predators.clone());
+            let (hash, vision) = (&world.spatial_hash, world.predator_vision);
+            let serial: Vec<_> = a.iter_mut().map(|p| p.hunt(&prey, hash, &mut Vec::new(), vision, day_phase, 1.0)).collect();
+            let parallel: Vec<_> = b.par_iter_mut().map_init(Vec::new, |nearby, p| p.hunt(&prey, hash, nearby, vision, day_phase, 1.0)).collect();
+            assert_eq!(serial, parallel, "tick {t}: catches differ");
+            for (p, q) in a.iter().zip(&b) {
+                assert_eq!(
+                    (p.acceleration.x, p.acceleration.y, p.max_speed),
+                    (q.acceleration.x, q.acceleration.y, q.max_speed),
+                    "tick {t}: predator state differs",
+                );
+            }
+        }
+    }
+}